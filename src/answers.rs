@@ -0,0 +1,116 @@
+use crate::common::*;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Known-good answers for each day, read from a TOML file of the form:
+///
+/// ```toml
+/// [day01]
+/// a = 1234
+/// b = "some text"
+/// ```
+#[derive(Debug, Default)]
+pub struct Manifest {
+    days: BTreeMap<String, BTreeMap<String, toml::Value>>,
+}
+
+/// An answer that differs from the one recorded in the manifest.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    pub part: Part,
+    pub expected: String,
+    pub actual: Option<Answer>,
+}
+
+impl Manifest {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to open {}", path.display()))?;
+
+        Self::parse(&content).with_context(|| format!("error while parsing {}", path.display()))
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        Ok(Self {
+            days: toml::from_str(content)?,
+        })
+    }
+
+    /// Returns the expected answer for the given day and part as a string.
+    pub fn expected(&self, day: usize, part: Part) -> Option<String> {
+        let key = format!("{}", part).to_ascii_lowercase();
+
+        match self.days.get(&format!("day{:02}", day))?.get(&key)? {
+            toml::Value::String(s) => Some(s.clone()),
+            other => Some(other.to_string()),
+        }
+    }
+
+    /// Compares the selected parts of `solution` against the manifest. Parts
+    /// without a known answer are not checked.
+    pub fn check(&self, day: usize, parts: Parts, solution: &Solution) -> Vec<Mismatch> {
+        let mut mismatches = vec![];
+
+        for &part in &Part::ALL {
+            if !parts.contains(part) {
+                continue;
+            }
+
+            let expected = match self.expected(day, part) {
+                Some(expected) => expected,
+                None => continue,
+            };
+
+            let actual = solution.get(part);
+            if actual.map(|a| a.to_string()) != Some(expected.clone()) {
+                mismatches.push(Mismatch {
+                    part,
+                    expected,
+                    actual: actual.cloned(),
+                });
+            }
+        }
+
+        mismatches
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let manifest = Manifest::parse("[day01]\na = 42\nb = \"abc\"\n\n[day02]\na = 7\n").unwrap();
+
+        assert_eq!(manifest.expected(1, Part::A), Some("42".to_string()));
+        assert_eq!(manifest.expected(1, Part::B), Some("abc".to_string()));
+        assert_eq!(manifest.expected(2, Part::B), None);
+        assert_eq!(manifest.expected(3, Part::A), None);
+
+        let solution = Solution {
+            part_a: Some(42.into()),
+            part_b: Some("abc".into()),
+        };
+        assert_eq!(manifest.check(1, Parts::BOTH, &solution), []);
+
+        let solution = Solution {
+            part_a: Some(Answer::Text("42".into())),
+            part_b: None,
+        };
+        let mismatches = manifest.check(1, Parts::BOTH, &solution);
+        assert_eq!(
+            mismatches,
+            [Mismatch {
+                part: Part::B,
+                expected: "abc".into(),
+                actual: None,
+            }]
+        );
+
+        let only_a = Parts { a: true, b: false };
+        assert_eq!(manifest.check(1, only_a, &solution), []);
+        assert_eq!(manifest.check(2, Parts::BOTH, &solution).len(), 1);
+    }
+}
@@ -0,0 +1,361 @@
+//! Two-state cellular automata with Life-like birth/survival rules.
+//!
+//! [`Sparse`] stores the live cells of an unbounded lattice in a set and works
+//! for any [`Coord`] type with a [`Neighborhood`], like [`moore`] in any number
+//! of dimensions. [`Dense`] stores a bounded [`Grid`] where some cells may be
+//! [`Cell::Inert`], and its neighborhood can depend on the grid itself.
+use crate::common::*;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::hash::Hash;
+use std::str::FromStr;
+
+/// A rule in `B/S` notation: a dead cell becomes alive if its number of live
+/// neighbors is in `birth`, a live cell stays alive if it is in `survival`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rule {
+    pub birth: BTreeSet<usize>,
+    pub survival: BTreeSet<usize>,
+}
+
+impl Rule {
+    pub fn new(birth: &[usize], survival: &[usize]) -> Self {
+        Self {
+            birth: birth.iter().copied().collect(),
+            survival: survival.iter().copied().collect(),
+        }
+    }
+
+    /// Conway's Game of Life, `B3/S23`.
+    pub fn life() -> Self {
+        Self::new(&[3], &[2, 3])
+    }
+
+    /// HighLife, `B36/S23`.
+    pub fn high_life() -> Self {
+        Self::new(&[3, 6], &[2, 3])
+    }
+
+    /// Whether a cell is alive in the next generation.
+    pub fn next(&self, alive: bool, neighbors: usize) -> bool {
+        if alive {
+            self.survival.contains(&neighbors)
+        } else {
+            self.birth.contains(&neighbors)
+        }
+    }
+}
+
+/// Parses `B3/S23`. Counts of ten or more neighbors must be separated by
+/// commas, as in `B3/S2,3,10`.
+impl FromStr for Rule {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parse_counts = |part: &str, prefix: char| -> Result<BTreeSet<usize>> {
+            let counts = part
+                .strip_prefix(prefix)
+                .or_else(|| part.strip_prefix(prefix.to_ascii_lowercase()))
+                .ok_or_else(|| anyhow!("expecting {:?} in rule {:?}", prefix, s))?;
+
+            if counts.contains(',') {
+                counts
+                    .split(',')
+                    .map(|n| n.trim().parse().map_err(Error::from))
+                    .collect()
+            } else {
+                counts
+                    .chars()
+                    .map(|c| c.to_digit(10).map(|n| n as usize))
+                    .collect::<Option<_>>()
+                    .ok_or_else(|| anyhow!("invalid neighbor counts {:?}", counts))
+            }
+        };
+
+        match s.trim().split_once('/') {
+            Some((birth, survival)) => Ok(Self {
+                birth: parse_counts(birth, 'B')?,
+                survival: parse_counts(survival, 'S')?,
+            }),
+            None => bail!("expecting a rule like \"B3/S23\", found {:?}", s),
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let fmt_counts = |counts: &BTreeSet<usize>| {
+            if counts.iter().all(|&n| n < 10) {
+                counts.iter().join("")
+            } else {
+                counts.iter().join(",")
+            }
+        };
+
+        write!(
+            f,
+            "B{}/S{}",
+            fmt_counts(&self.birth),
+            fmt_counts(&self.survival)
+        )
+    }
+}
+
+/// A position on a lattice that can be offset by another position.
+pub trait Coord: Copy + Eq + Hash {
+    fn offset(self, by: Self) -> Self;
+}
+
+impl<const N: usize> Coord for [i32; N] {
+    fn offset(mut self, by: Self) -> Self {
+        for (a, b) in self.iter_mut().zip(&by) {
+            *a += b;
+        }
+
+        self
+    }
+}
+
+impl Coord for (i32, i32) {
+    fn offset(self, by: Self) -> Self {
+        (self.0 + by.0, self.1 + by.1)
+    }
+}
+
+/// The cells that count as neighbors of a cell.
+pub trait Neighborhood<C> {
+    fn for_each_neighbor(&self, c: C, f: &mut dyn FnMut(C));
+}
+
+/// A neighborhood given by a list of offsets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Offsets<C>(pub Vec<C>);
+
+impl<C: Coord> Neighborhood<C> for Offsets<C> {
+    fn for_each_neighbor(&self, c: C, f: &mut dyn FnMut(C)) {
+        for &d in &self.0 {
+            f(c.offset(d));
+        }
+    }
+}
+
+/// The Moore neighborhood in `N` dimensions: all `3^N - 1` cells that differ by
+/// at most one in every coordinate.
+pub fn moore<const N: usize>() -> Offsets<[i32; N]> {
+    let mut offsets = vec![[0; N]];
+
+    for axis in 0..N {
+        offsets = offsets
+            .into_iter()
+            .flat_map(|d| {
+                (-1..=1).map(move |v| {
+                    let mut d = d;
+                    d[axis] = v;
+                    d
+                })
+            })
+            .collect();
+    }
+
+    offsets.retain(|d| d.iter().any(|&v| v != 0));
+    Offsets(offsets)
+}
+
+/// The von Neumann neighborhood in `N` dimensions: the `2N` cells that differ by
+/// one in a single coordinate.
+pub fn von_neumann<const N: usize>() -> Offsets<[i32; N]> {
+    let offsets = (0..N)
+        .flat_map(|axis| {
+            [-1, 1].iter().map(move |&v| {
+                let mut d = [0; N];
+                d[axis] = v;
+                d
+            })
+        })
+        .collect();
+
+    Offsets(offsets)
+}
+
+/// An unbounded automaton that stores only its live cells.
+#[derive(Clone, Debug)]
+pub struct Sparse<C, N> {
+    alive: HashSet<C>,
+    neighborhood: N,
+}
+
+impl<C: Coord, N: Neighborhood<C>> Sparse<C, N> {
+    pub fn new(alive: impl IntoIterator<Item = C>, neighborhood: N) -> Self {
+        Self {
+            alive: alive.into_iter().collect(),
+            neighborhood,
+        }
+    }
+
+    pub fn alive(&self) -> &HashSet<C> {
+        &self.alive
+    }
+
+    pub fn population(&self) -> usize {
+        self.alive.len()
+    }
+
+    /// Computes the next generation and returns whether anything changed.
+    /// Only cells next to a live cell can be born, so `B0` has no effect.
+    pub fn step(&mut self, rule: &Rule) -> bool {
+        let mut count = HashMap::<C, usize>::default();
+
+        for &c in &self.alive {
+            count.entry(c).or_default();
+            self.neighborhood
+                .for_each_neighbor(c, &mut |n| *count.entry(n).or_default() += 1);
+        }
+
+        let next: HashSet<C> = count
+            .into_iter()
+            .filter(|&(c, n)| rule.next(self.alive.contains(&c), n))
+            .map(|(c, _)| c)
+            .collect();
+
+        let changed = next != self.alive;
+        self.alive = next;
+        changed
+    }
+}
+
+/// A cell of a [`Dense`] automaton.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Cell {
+    Dead,
+    Alive,
+    /// Never changes and never counts as a live neighbor.
+    Inert,
+}
+
+/// A bounded automaton on a grid, where the neighbors of every cell are
+/// computed once when it is created.
+#[derive(Clone, Debug)]
+pub struct Dense {
+    cells: Grid<Cell>,
+    neighbors: Grid<Vec<[usize; 2]>>,
+}
+
+impl Dense {
+    /// Creates an automaton where `neighborhood` gives the neighbors of a
+    /// position, like [`adjacent`] or [`line_of_sight`].
+    pub fn new<F>(cells: Grid<Cell>, neighborhood: F) -> Self
+    where
+        F: Fn(&Grid<Cell>, [usize; 2]) -> Vec<[usize; 2]>,
+    {
+        let neighbors = cells.map_indexed(|pos, _| neighborhood(&cells, pos));
+        Self { cells, neighbors }
+    }
+
+    pub fn cells(&self) -> &Grid<Cell> {
+        &self.cells
+    }
+
+    pub fn population(&self) -> usize {
+        self.cells.count(|&c| c == Cell::Alive)
+    }
+
+    /// Computes the next generation and returns whether anything changed.
+    pub fn step(&mut self, rule: &Rule) -> bool {
+        let old = &self.cells;
+        let next = old.map_indexed(|pos, &cell| {
+            let alive = match cell {
+                Cell::Inert => return Cell::Inert,
+                cell => cell == Cell::Alive,
+            };
+
+            let n = self.neighbors[pos]
+                .iter()
+                .filter(|&&p| old[p] == Cell::Alive)
+                .count();
+
+            if rule.next(alive, n) {
+                Cell::Alive
+            } else {
+                Cell::Dead
+            }
+        });
+
+        let changed = next != self.cells;
+        self.cells = next;
+        changed
+    }
+}
+
+/// The up to eight cells around `pos`.
+pub fn adjacent(grid: &Grid<Cell>, pos: [usize; 2]) -> Vec<[usize; 2]> {
+    grid.neighbors8(pos).collect()
+}
+
+/// The first cell that is not inert in each of the eight directions.
+pub fn line_of_sight(grid: &Grid<Cell>, pos: [usize; 2]) -> Vec<[usize; 2]> {
+    DIRECTIONS_8
+        .iter()
+        .filter_map(|&dir| grid.cast(pos, dir, |&c| c != Cell::Inert))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rule() {
+        let rule: Rule = "B36/S23".parse().unwrap();
+        assert_eq!(rule, Rule::high_life());
+        assert_eq!(rule.to_string(), "B36/S23");
+
+        let rule: Rule = "b3/s2,3,10".parse().unwrap();
+        assert_eq!(rule, Rule::new(&[3], &[2, 3, 10]));
+        assert_eq!(rule.to_string(), "B3/S2,3,10");
+
+        assert!("B3S23".parse::<Rule>().is_err());
+        assert!("B3/Sx".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn test_sparse() {
+        assert_eq!(moore::<2>().0.len(), 8);
+        assert_eq!(moore::<4>().0.len(), 80);
+        assert_eq!(von_neumann::<3>().0.len(), 6);
+
+        // A blinker oscillates with period two.
+        let blinker = [[0, -1], [0, 0], [0, 1]];
+        let mut life = Sparse::new(blinker.iter().copied(), moore::<2>());
+
+        assert!(life.step(&Rule::life()));
+        assert_eq!(
+            life.alive(),
+            &[[-1, 0], [0, 0], [1, 0]].iter().copied().collect()
+        );
+        life.step(&Rule::life());
+        assert_eq!(life.alive(), &blinker.iter().copied().collect());
+
+        // A block is stable.
+        let mut life = Sparse::new(vec![[0, 0], [0, 1], [1, 0], [1, 1]], moore::<2>());
+        assert!(!life.step(&Rule::life()));
+        assert_eq!(life.population(), 4);
+    }
+
+    #[test]
+    fn test_dense() {
+        let grid = Grid::parse("#.#\n...\nL.#").unwrap().map(|&c| match c {
+            '#' => Cell::Alive,
+            'L' => Cell::Dead,
+            _ => Cell::Inert,
+        });
+
+        assert_eq!(adjacent(&grid, [0, 0]).len(), 3);
+        assert_eq!(line_of_sight(&grid, [0, 0]), [[0, 2], [2, 0], [2, 2]]);
+
+        // Every live cell sees two others, the dead cell sees three.
+        let mut automaton = Dense::new(grid, line_of_sight);
+        assert!(automaton.step(&Rule::life()));
+        assert_eq!(automaton.population(), 4);
+        assert!(!automaton.step(&Rule::life()));
+    }
+}
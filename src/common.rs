@@ -3,31 +3,256 @@ pub use itertools::{all, any, enumerate, max, min, zip, Itertools};
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::cmp::{Ord, Ordering};
+use std::convert::TryFrom;
 use std::default::Default;
+use std::env;
+use std::fmt;
 use std::iter::{Map, Sum};
 pub use std::mem::swap;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
+mod grid;
+mod transform;
+pub use grid::{Grid, DIRECTIONS_4, DIRECTIONS_8};
+pub use transform::Transform;
+
 pub type HashMap<K, V> = std::collections::HashMap<K, V, fnv::FnvBuildHasher>;
 pub type HashSet<K> = std::collections::HashSet<K, fnv::FnvBuildHasher>;
 pub type Result<T = (), E = Error> = std::result::Result<T, E>;
 
-#[allow(dead_code)]
+/// One of the two parts of a puzzle.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    A,
+    B,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::A, Part::B];
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Part::A => write!(f, "A"),
+            Part::B => write!(f, "B"),
+        }
+    }
+}
+
+/// Selects which parts of a puzzle should be solved.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Parts {
+    pub a: bool,
+    pub b: bool,
+}
+
+impl Parts {
+    pub const BOTH: Parts = Parts { a: true, b: true };
+
+    pub fn contains(self, part: Part) -> bool {
+        match part {
+            Part::A => self.a,
+            Part::B => self.b,
+        }
+    }
+}
+
+/// The answer to one part of a puzzle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Answer {
+    Int(i128),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Int(v) => write!(f, "{}", v),
+            Answer::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+macro_rules! impl_answer_from_int {
+    ($($t:ty)*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(v: $t) -> Self {
+                    Answer::Int(v as i128)
+                }
+            }
+        )*
+    };
+}
+
+impl_answer_from_int!(i32 i64 i128 u32 u64 usize);
+
+/// Values beyond `i128::MAX` are kept as text.
+impl From<u128> for Answer {
+    fn from(v: u128) -> Self {
+        match i128::try_from(v) {
+            Ok(v) => Answer::Int(v),
+            Err(_) => Answer::Text(v.to_string()),
+        }
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Answer::Text(s.to_string())
+    }
+}
+
+/// The answers to both parts of a puzzle. Parts that were not solved are `None`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Solution {
+    pub part_a: Option<Answer>,
+    pub part_b: Option<Answer>,
+}
+
+impl Solution {
+    pub fn get(&self, part: Part) -> Option<&Answer> {
+        match part {
+            Part::A => self.part_a.as_ref(),
+            Part::B => self.part_b.as_ref(),
+        }
+    }
+}
+
 pub fn default<T: Default>() -> T {
     T::default()
 }
 
-pub fn read_input(filename: &str) -> Result<Vec<String>> {
+/// Environment variable that overrides the default input directory.
+pub const INPUT_DIR_VAR: &str = "AOC_INPUT_DIR";
+
+/// Where the input of a puzzle is read from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InputSource {
+    File(PathBuf),
+    Stdin,
+}
+
+impl InputSource {
+    /// Parses a path given on the command line, where `-` means stdin.
+    pub fn parse(s: &str) -> Self {
+        if s == "-" {
+            InputSource::Stdin
+        } else {
+            InputSource::File(s.into())
+        }
+    }
+}
+
+#[derive(Default)]
+struct InputConfig {
+    dir: Option<PathBuf>,
+    sources: HashMap<String, InputSource>,
+}
+
+lazy_static! {
+    static ref INPUT_CONFIG: Mutex<InputConfig> = Mutex::default();
+}
+
+/// Sets the directory that inputs are read from. This takes precedence over the
+/// `AOC_INPUT_DIR` environment variable.
+pub fn set_input_dir(dir: impl Into<PathBuf>) {
+    INPUT_CONFIG.lock().unwrap().dir = Some(dir.into());
+}
+
+/// Reads the input with the given name from `source` instead of the input directory.
+pub fn set_input_source(filename: &str, source: InputSource) {
+    let mut guard = INPUT_CONFIG.lock().unwrap();
+    guard.sources.insert(filename.to_string(), source);
+}
+
+fn input_source(filename: &str) -> InputSource {
+    let guard = INPUT_CONFIG.lock().unwrap();
+
+    if let Some(source) = guard.sources.get(filename) {
+        return source.clone();
+    }
+
+    let dir = match (&guard.dir, env::var_os(INPUT_DIR_VAR)) {
+        (Some(dir), _) => dir.clone(),
+        (None, Some(dir)) => dir.into(),
+        (None, None) => Path::new(env!("CARGO_MANIFEST_DIR")).join("src/inputs"),
+    };
+
+    InputSource::File(dir.join(filename))
+}
+
+pub fn read_input_string(filename: &str) -> Result<String> {
     use std::fs::File;
-    use std::io::{BufRead, BufReader};
+    use std::io::{stdin, Read};
+
+    let mut content = String::new();
+
+    match input_source(filename) {
+        InputSource::File(path) => {
+            let mut f =
+                File::open(&path).with_context(|| format!("failed to open {}", path.display()))?;
 
-    let path = format!("inputs/{}", filename);
-    let f = File::open(&path).with_context(|| format!("failed to open {}", path))?;
+            f.read_to_string(&mut content)
+                .with_context(|| format!("error while reading {}", path.display()))?;
+        }
+        InputSource::Stdin => {
+            stdin()
+                .read_to_string(&mut content)
+                .context("error while reading stdin")?;
+        }
+    }
+
+    Ok(content)
+}
 
-    BufReader::new(f)
+pub fn read_input(filename: &str) -> Result<Vec<String>> {
+    Ok(read_input_string(filename)?
         .lines()
-        .collect::<Result<_, _>>()
-        .with_context(|| format!("error while reading {}", path))
+        .map(str::to_string)
+        .collect())
+}
+
+/// A solver for the puzzle of a single day.
+pub trait Solver {
+    /// The day of the puzzle, starting at 1.
+    const DAY: usize;
+
+    /// Whether the puzzle has a second part. Only day 25 does not.
+    const HAS_PART_B: bool = true;
+
+    /// The puzzle input after parsing.
+    type Input;
+
+    fn parse(input: &str) -> Result<Self::Input>;
+
+    fn part_a(input: &Self::Input) -> Result<Answer>;
+
+    fn part_b(input: &Self::Input) -> Result<Answer>;
+}
+
+/// Reads the input of solver `S` and solves the selected parts.
+pub fn run<S: Solver>(parts: Parts) -> Result<Solution> {
+    let input = S::parse(&read_input_string(&format!("day{:02}", S::DAY))?)?;
+    let mut solution = Solution::default();
+
+    if parts.a {
+        solution.part_a = Some(S::part_a(&input).context("while solving part A")?);
+    }
+
+    if parts.b && S::HAS_PART_B {
+        solution.part_b = Some(S::part_b(&input).context("while solving part B")?);
+    }
+
+    Ok(solution)
 }
 
 pub fn cmp<T: Ord>(lhs: T, rhs: T) -> Ordering {
@@ -66,17 +291,10 @@ fn compile(pattern: &str) -> &'static Regex {
     result
 }
 
-#[allow(dead_code)]
-pub fn is_match(pattern: &str, string: &str) -> bool {
-    compile(pattern).is_match(string)
-}
-
-#[allow(dead_code)]
 pub fn find<'t>(pattern: &str, string: &'t str) -> Option<regex::Captures<'t>> {
     compile(pattern).captures(string)
 }
 
-#[allow(dead_code)]
 pub fn find_all<'t>(pattern: &str, string: &'t str) -> regex::CaptureMatches<'static, 't> {
     compile(pattern).captures_iter(string)
 }
@@ -0,0 +1,295 @@
+use super::*;
+use ndarray::prelude::*;
+use std::ops::{Index, IndexMut};
+
+/// Offsets of the four orthogonal neighbors, as `[row, column]`.
+pub const DIRECTIONS_4: [[isize; 2]; 4] = [[-1, 0], [0, 1], [1, 0], [0, -1]];
+
+/// Offsets of the eight neighbors including diagonals, as `[row, column]`.
+pub const DIRECTIONS_8: [[isize; 2]; 8] = [
+    [-1, -1],
+    [-1, 0],
+    [-1, 1],
+    [0, 1],
+    [0, -1],
+    [1, -1],
+    [1, 0],
+    [1, 1],
+];
+
+/// A rectangular grid of cells, indexed by `[row, column]`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Array2<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn from_elem(rows: usize, cols: usize, elem: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            cells: Array2::from_elem((rows, cols), elem),
+        }
+    }
+
+    /// Parses one row per line, converting every character with `f`. All lines
+    /// must have the same length.
+    pub fn parse_with<F>(input: &str, mut f: F) -> Result<Self>
+    where
+        F: FnMut(char) -> Result<T>,
+    {
+        let lines: Vec<_> = input.lines().collect();
+        let cols = lines.first().map_or(0, |l| l.chars().count());
+        let mut cells = Vec::with_capacity(lines.len() * cols);
+
+        for (i, line) in enumerate(&lines) {
+            let len = line.chars().count();
+            if len != cols {
+                bail!(
+                    "line {} has length {}, expecting {} like the first line",
+                    i + 1,
+                    len,
+                    cols
+                );
+            }
+
+            for c in line.chars() {
+                cells.push(f(c).with_context(|| format!("on line {}", i + 1))?);
+            }
+        }
+
+        let cells = Array2::from_shape_vec((lines.len(), cols), cells)?;
+        Ok(Self { cells })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.cells.nrows()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cells.ncols()
+    }
+
+    /// The number of rows and columns.
+    pub fn dim(&self) -> [usize; 2] {
+        [self.rows(), self.cols()]
+    }
+
+    pub fn view(&self) -> ArrayView2<'_, T> {
+        self.cells.view()
+    }
+
+    pub fn view_mut(&mut self) -> ArrayViewMut2<'_, T> {
+        self.cells.view_mut()
+    }
+
+    pub fn into_array(self) -> Array2<T> {
+        self.cells
+    }
+
+    pub fn get(&self, [i, j]: [usize; 2]) -> Option<&T> {
+        self.cells.get([i, j])
+    }
+
+    /// The cell at `pos`, where the grid repeats itself infinitely in every
+    /// direction. Only an empty grid has no cells.
+    pub fn get_wrapping(&self, [i, j]: [isize; 2]) -> Option<&T> {
+        let [rows, cols] = self.dim();
+        if rows == 0 || cols == 0 {
+            return None;
+        }
+
+        let i = i.rem_euclid(rows as isize) as usize;
+        let j = j.rem_euclid(cols as isize) as usize;
+        self.cells.get([i, j])
+    }
+
+    /// The position `steps` times `dir` away from `pos`, if it is inside the grid.
+    pub fn offset(
+        &self,
+        [i, j]: [usize; 2],
+        [di, dj]: [isize; 2],
+        steps: isize,
+    ) -> Option<[usize; 2]> {
+        let [rows, cols] = self.dim();
+        let ni = i as isize + di * steps;
+        let nj = j as isize + dj * steps;
+
+        if ni >= 0 && ni < rows as isize && nj >= 0 && nj < cols as isize {
+            Some([ni as usize, nj as usize])
+        } else {
+            None
+        }
+    }
+
+    /// The positions next to `pos` in the given directions that are inside the
+    /// grid.
+    pub fn neighbors<'a>(
+        &'a self,
+        pos: [usize; 2],
+        dirs: &'a [[isize; 2]],
+    ) -> impl Iterator<Item = [usize; 2]> + 'a {
+        dirs.iter().filter_map(move |&dir| self.offset(pos, dir, 1))
+    }
+
+    pub fn neighbors4(&self, pos: [usize; 2]) -> impl Iterator<Item = [usize; 2]> + '_ {
+        self.neighbors(pos, &DIRECTIONS_4)
+    }
+
+    pub fn neighbors8(&self, pos: [usize; 2]) -> impl Iterator<Item = [usize; 2]> + '_ {
+        self.neighbors(pos, &DIRECTIONS_8)
+    }
+
+    /// The positions from `pos` in direction `dir` until the edge of the grid,
+    /// not including `pos` itself. Panics if `dir` is zero, as the ray would
+    /// never leave `pos`.
+    pub fn ray(&self, pos: [usize; 2], dir: [isize; 2]) -> impl Iterator<Item = [usize; 2]> + '_ {
+        assert_ne!(dir, [0, 0], "a ray needs a direction");
+        (1..).map_while(move |steps| self.offset(pos, dir, steps))
+    }
+
+    /// The first position from `pos` in direction `dir` whose cell matches
+    /// `pred`. Panics if `dir` is zero, like [`Grid::ray`].
+    pub fn cast<F>(&self, pos: [usize; 2], dir: [isize; 2], mut pred: F) -> Option<[usize; 2]>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.ray(pos, dir).find(|&p| pred(&self[p]))
+    }
+
+    /// All positions in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = [usize; 2]> {
+        let [rows, cols] = self.dim();
+        (0..rows).flat_map(move |i| (0..cols).map(move |j| [i, j]))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn indexed_iter(&self) -> impl Iterator<Item = ([usize; 2], &T)> {
+        self.cells.indexed_iter().map(|((i, j), v)| ([i, j], v))
+    }
+
+    /// A grid of the same size with `f` applied to every cell.
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid {
+            cells: self.cells.map(f),
+        }
+    }
+
+    /// A grid of the same size with `f` applied to every position.
+    pub fn map_indexed<U, F>(&self, mut f: F) -> Grid<U>
+    where
+        F: FnMut([usize; 2], &T) -> U,
+    {
+        let cells = self.indexed_iter().map(|(p, v)| f(p, v)).collect();
+        Grid {
+            cells: Array2::from_shape_vec(self.cells.raw_dim(), cells).unwrap(),
+        }
+    }
+
+    /// The grid rotated or reflected by `t`.
+    pub fn transform(&self, t: Transform) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            cells: t.apply(self.view()).to_owned(),
+        }
+    }
+
+    pub fn count<F>(&self, mut pred: F) -> usize
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.iter().filter(|v| pred(v)).count()
+    }
+}
+
+impl Grid<char> {
+    /// Parses one row of characters per line.
+    pub fn parse(input: &str) -> Result<Self> {
+        Self::parse_with(input, Ok)
+    }
+}
+
+impl<T> From<Array2<T>> for Grid<T> {
+    fn from(cells: Array2<T>) -> Self {
+        Self { cells }
+    }
+}
+
+impl<T> Index<[usize; 2]> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: [usize; 2]) -> &T {
+        &self.cells[pos]
+    }
+}
+
+impl<T> IndexMut<[usize; 2]> for Grid<T> {
+    fn index_mut(&mut self, pos: [usize; 2]) -> &mut T {
+        &mut self.cells[pos]
+    }
+}
+
+/// Prints every row on its own line, without separators between cells.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.cells.genrows() {
+            for v in row {
+                write!(f, "{}", v)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_grid() {
+        let grid = Grid::parse("#..\n.L.\n..#\n...").unwrap();
+        assert_eq!(grid.dim(), [4, 3]);
+        assert_eq!(grid[[1, 1]], 'L');
+        assert_eq!(grid.to_string(), "#..\n.L.\n..#\n...\n");
+        assert_eq!(grid.count(|&c| c == '#'), 2);
+
+        assert_eq!(grid.neighbors4([0, 0]).collect_vec(), [[0, 1], [1, 0]]);
+        assert_eq!(grid.neighbors8([1, 1]).count(), 8);
+        assert_eq!(grid.neighbors8([3, 2]).count(), 3);
+
+        assert_eq!(grid.get_wrapping([4, 3]), Some(&'#'));
+        assert_eq!(grid.get_wrapping([-2, -1]), Some(&'#'));
+        assert_eq!(Grid::parse("").unwrap().get_wrapping([0, 0]), None);
+
+        assert_eq!(grid.ray([0, 0], [1, 1]).collect_vec(), [[1, 1], [2, 2]]);
+        assert_eq!(grid.cast([0, 0], [1, 1], |&c| c == '#'), Some([2, 2]));
+        assert_eq!(grid.cast([0, 0], [0, 1], |&c| c == '#'), None);
+
+        let turned = grid.transform(Transform::Rot90);
+        assert_eq!(turned.to_string(), "...#\n..L.\n.#..\n");
+
+        let counts = grid.map_indexed(|p, _| grid.neighbors8(p).count());
+        assert_eq!(counts[[0, 0]], 3);
+
+        assert!(Grid::parse("..\n...").is_err());
+        assert!(Grid::parse_with("12\n3x", |c| c.to_digit(10).context("not a digit")).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "a ray needs a direction")]
+    fn test_ray_without_direction() {
+        let grid = Grid::parse("#.\n.#").unwrap();
+        grid.cast([0, 0], [0, 0], |&c| c == '#');
+    }
+}
@@ -0,0 +1,179 @@
+use super::*;
+use ndarray::prelude::*;
+use std::str::FromStr;
+
+/// One of the eight rotations and reflections of a rectangle, the symmetries
+/// of a square. Rotations are clockwise.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Transform {
+    Identity,
+    Rot90,
+    Rot180,
+    Rot270,
+    /// Mirrors left and right.
+    FlipH,
+    /// Mirrors top and bottom.
+    FlipV,
+    /// Mirrors along the main diagonal.
+    Transpose,
+    /// Mirrors along the other diagonal.
+    AntiTranspose,
+}
+
+impl Transform {
+    pub const ALL: [Transform; 8] = [
+        Transform::Identity,
+        Transform::Rot90,
+        Transform::Rot180,
+        Transform::Rot270,
+        Transform::FlipH,
+        Transform::FlipV,
+        Transform::Transpose,
+        Transform::AntiTranspose,
+    ];
+
+    /// Whether the axes are swapped, and then whether the rows and the
+    /// columns are reversed.
+    fn parts(self) -> (bool, bool, bool) {
+        use Transform::*;
+
+        match self {
+            Identity => (false, false, false),
+            Rot90 => (true, false, true),
+            Rot180 => (false, true, true),
+            Rot270 => (true, true, false),
+            FlipH => (false, false, true),
+            FlipV => (false, true, false),
+            Transpose => (true, false, false),
+            AntiTranspose => (true, true, true),
+        }
+    }
+
+    fn from_parts(parts: (bool, bool, bool)) -> Self {
+        *Self::ALL.iter().find(|t| t.parts() == parts).unwrap()
+    }
+
+    /// The transform that applies `self` and then `other`.
+    pub fn then(self, other: Transform) -> Self {
+        let (t1, r1, c1) = self.parts();
+        let (t2, r2, c2) = other.parts();
+
+        // Swapping the axes turns a reversal of the rows into one of the
+        // columns and the other way around.
+        let (r1, c1) = if t2 { (c1, r1) } else { (r1, c1) };
+        Self::from_parts((t1 ^ t2, r1 ^ r2, c1 ^ c2))
+    }
+
+    /// The transform that undoes `self`.
+    pub fn inverse(self) -> Self {
+        let (t, r, c) = self.parts();
+        Self::from_parts(if t { (t, c, r) } else { (t, r, c) })
+    }
+
+    /// A view of `array` after the transform, without copying it.
+    pub fn apply<'a, T>(self, array: ArrayView2<'a, T>) -> ArrayView2<'a, T> {
+        let (transpose, rows, cols) = self.parts();
+        let mut m = array;
+
+        if transpose {
+            m = m.reversed_axes();
+        }
+
+        if rows {
+            m.invert_axis(Axis(0));
+        }
+
+        if cols {
+            m.invert_axis(Axis(1));
+        }
+
+        m
+    }
+
+    /// The shape of an array of shape `dim` after the transform.
+    pub fn apply_dim(self, [rows, cols]: [usize; 2]) -> [usize; 2] {
+        if self.parts().0 {
+            [cols, rows]
+        } else {
+            [rows, cols]
+        }
+    }
+
+    /// Where the cell at `pos` of an array of shape `dim` ends up.
+    pub fn apply_pos(self, [i, j]: [usize; 2], dim: [usize; 2]) -> [usize; 2] {
+        let (transpose, rows, cols) = self.parts();
+        let [i, j] = if transpose { [j, i] } else { [i, j] };
+        let [h, w] = self.apply_dim(dim);
+
+        [
+            if rows { h - 1 - i } else { i },
+            if cols { w - 1 - j } else { j },
+        ]
+    }
+}
+
+impl FromStr for Transform {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|t| t.to_string() == s)
+            .ok_or_else(|| anyhow!("invalid transform {:?}", s))
+    }
+}
+
+impl fmt::Display for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Transform::*;
+
+        let s = match self {
+            Identity => "identity",
+            Rot90 => "rot90",
+            Rot180 => "rot180",
+            Rot270 => "rot270",
+            FlipH => "flip-h",
+            FlipV => "flip-v",
+            Transpose => "transpose",
+            AntiTranspose => "anti-transpose",
+        };
+
+        f.pad(s)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_transform() {
+        use Transform::*;
+
+        let a = Array2::from_shape_vec((2, 3), (0..6).collect()).unwrap();
+        assert_eq!(Rot90.apply(a.view()), array![[3, 0], [4, 1], [5, 2]]);
+        assert_eq!(Rot270.apply(a.view()), array![[2, 5], [1, 4], [0, 3]]);
+        assert_eq!(FlipH.apply(a.view()), array![[2, 1, 0], [5, 4, 3]]);
+        assert_eq!(
+            AntiTranspose.apply(a.view()),
+            array![[5, 2], [4, 1], [3, 0]]
+        );
+        assert_eq!(Rot90.then(Rot90).then(Rot90), Rot270);
+        assert_eq!(FlipH.then(Rot90), AntiTranspose);
+
+        for &t in &Transform::ALL {
+            let b = t.apply(a.view());
+            assert_eq!(t.inverse().apply(b), a);
+            assert_eq!(t.to_string().parse::<Transform>().unwrap(), t);
+
+            for ((i, j), &v) in a.indexed_iter() {
+                assert_eq!(b[t.apply_pos([i, j], [2, 3])], v);
+            }
+
+            for &u in &Transform::ALL {
+                assert_eq!(u.apply(b), t.then(u).apply(a.view()));
+            }
+        }
+    }
+}
@@ -1,17 +1,6 @@
 use crate::common::*;
 use std::cmp::Ordering::*;
 
-fn parse_input(filename: &str) -> Result<Vec<usize>> {
-    let mut numbers = read_input(filename)?
-        .into_iter()
-        .filter(|x| !x.is_empty())
-        .map(|x| x.parse().context("invalid number"))
-        .collect::<Result<Vec<_>, _>>()?;
-
-    numbers.sort_unstable();
-    Ok(numbers)
-}
-
 fn find_two(numbers: &[usize], sum: usize) -> Option<[usize; 2]> {
     let (mut i, mut j) = (0, numbers.len() - 1);
 
@@ -40,20 +29,40 @@ fn find_three(numbers: &[usize], sum: usize) -> Option<[usize; 3]> {
     None
 }
 
-pub fn run() -> Result {
-    let numbers = parse_input("day01")?;
+pub struct Day01;
 
-    let [a, b] = find_two(&numbers, 2020)
-        .ok_or_else(|| anyhow!("failed to find two numbers that sum to 2020"))?;
+impl Solver for Day01 {
+    const DAY: usize = 1;
+    type Input = Vec<usize>;
 
-    println!("parts A: {}", a * b);
+    fn parse(input: &str) -> Result<Self::Input> {
+        let mut numbers = input
+            .lines()
+            .filter(|x| !x.is_empty())
+            .map(|x| x.parse().context("invalid number"))
+            .collect::<Result<Vec<_>, _>>()?;
 
-    let [a, b, c] = find_three(&numbers, 2020)
-        .ok_or_else(|| anyhow!("failed to find three numbers that sum to 2020"))?;
+        if numbers.is_empty() {
+            bail!("no numbers");
+        }
 
-    println!("parts B: {}", a * b * c);
+        numbers.sort_unstable();
+        Ok(numbers)
+    }
+
+    fn part_a(numbers: &Self::Input) -> Result<Answer> {
+        let [a, b] = find_two(numbers, 2020)
+            .ok_or_else(|| anyhow!("failed to find two numbers that sum to 2020"))?;
 
-    Ok(())
+        Ok((a * b).into())
+    }
+
+    fn part_b(numbers: &Self::Input) -> Result<Answer> {
+        let [a, b, c] = find_three(numbers, 2020)
+            .ok_or_else(|| anyhow!("failed to find three numbers that sum to 2020"))?;
+
+        Ok((a * b * c).into())
+    }
 }
 
 #[cfg(test)]
@@ -78,4 +87,11 @@ mod test {
         let [a, b, c] = find_three(&numbers(), 2020).expect("to find three numbers");
         assert_eq!([a, b, c], [366, 675, 979]);
     }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Day01::parse("979\n366\n").unwrap(), [366, 979]);
+        assert!(Day01::parse("").is_err());
+        assert!(Day01::parse("\n\n").is_err());
+    }
 }
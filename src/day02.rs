@@ -3,7 +3,7 @@ use lazy_static::lazy_static;
 use regex::Regex;
 
 #[derive(PartialEq, Eq, Debug)]
-struct Password {
+pub struct Password {
     lowest: usize,
     highest: usize,
     letter: char,
@@ -43,27 +43,33 @@ fn is_valid_official_toboggan_corporate(w: &Password) -> bool {
     a ^ b
 }
 
-pub fn run() -> Result {
-    let lines = read_input("day02")?
-        .iter()
-        .map(|s| parse_line(s))
-        .collect::<Result<Vec<_>>>()?;
+pub struct Day02;
 
-    let count_valid = lines
-        .iter()
-        .filter(|p| is_valid_sled_rental_place(p))
-        .count();
+impl Solver for Day02 {
+    const DAY: usize = 2;
+    type Input = Vec<Password>;
 
-    println!("part A: {}", count_valid);
+    fn parse(input: &str) -> Result<Self::Input> {
+        input.lines().map(parse_line).collect()
+    }
+
+    fn part_a(lines: &Self::Input) -> Result<Answer> {
+        let count_valid = lines
+            .iter()
+            .filter(|p| is_valid_sled_rental_place(p))
+            .count();
 
-    let count_valid = lines
-        .iter()
-        .filter(|p| is_valid_official_toboggan_corporate(p))
-        .count();
+        Ok(count_valid.into())
+    }
 
-    println!("part B: {}", count_valid);
+    fn part_b(lines: &Self::Input) -> Result<Answer> {
+        let count_valid = lines
+            .iter()
+            .filter(|p| is_valid_official_toboggan_corporate(p))
+            .count();
 
-    Ok(())
+        Ok(count_valid.into())
+    }
 }
 
 #[cfg(test)]
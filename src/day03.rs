@@ -1,52 +1,35 @@
 use crate::common::*;
-use ndarray::prelude::*;
 
-fn parse_input(lines: Vec<String>) -> Result<Array2<char>> {
-    let height = lines.len();
-    let width = lines[0].len();
-
-    if !all(&lines, |v| v.chars().count() == width) {
-        return Err(anyhow!("not all lines have equal length"));
-    }
-
-    let mut result = Array2::default((height, width));
-
-    for (i, line) in enumerate(lines) {
-        for (j, c) in enumerate(line.chars()) {
-            result[[i, j]] = c;
-        }
-    }
-
-    Ok(result)
+fn count_trees(grid: &Grid<char>, shift_per_row: usize, shift_per_col: usize) -> usize {
+    (0..grid.rows())
+        .step_by(shift_per_col)
+        .enumerate()
+        .filter(|&(step, y)| {
+            grid.get_wrapping([y as isize, (step * shift_per_row) as isize]) == Some(&'#')
+        })
+        .count()
 }
 
-fn count_trees(matrix: ArrayView2<char>, shift_per_row: usize, shift_per_col: usize) -> usize {
-    let mut x = 0;
-    let (height, width) = matrix.dim();
-    let mut trees = 0;
+pub struct Day03;
 
-    for y in (0..height).step_by(shift_per_col) {
-        if matrix[[y, x]] == '#' {
-            trees += 1;
-        }
+impl Solver for Day03 {
+    const DAY: usize = 3;
+    type Input = Grid<char>;
 
-        x = (x + shift_per_row) % width;
+    fn parse(input: &str) -> Result<Self::Input> {
+        Grid::parse(input)
     }
 
-    trees
-}
-
-pub fn run() -> Result {
-    let matrix = parse_input(read_input("day03")?)?;
-
-    let trees = count_trees(matrix.view(), 3, 1);
-    println!("part A: {}", trees);
-
-    let total: usize = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)]
-        .iter()
-        .map(|&shift| count_trees(matrix.view(), shift.0, shift.1))
-        .product();
-    println!("part B: {:?}", total);
+    fn part_a(grid: &Self::Input) -> Result<Answer> {
+        let trees = count_trees(grid, 3, 1);
+        Ok(trees.into())
+    }
 
-    Ok(())
+    fn part_b(grid: &Self::Input) -> Result<Answer> {
+        let total: usize = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)]
+            .iter()
+            .map(|&shift| count_trees(grid, shift.0, shift.1))
+            .product();
+        Ok(total.into())
+    }
 }
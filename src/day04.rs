@@ -3,33 +3,15 @@ use lazy_static::lazy_static;
 use regex::Regex;
 use std::collections::HashMap;
 
-fn parse_input(content: &str) -> Vec<HashMap<&str, &str>> {
-    lazy_static! {
-        static ref SPLIT_LINES: Regex = Regex::new("[ ]*\n[ ]*\n[ ]*").unwrap();
-        static ref FIELD: Regex = Regex::new("([^ \n:]*):([^ \n]*)").unwrap();
-    }
-
-    let mut result = vec![];
-    for chunk in SPLIT_LINES.split(&content) {
-        let mut fields = HashMap::new();
+type Passport = HashMap<String, String>;
 
-        for part in FIELD.captures_iter(chunk) {
-            fields.insert(part.get(1).unwrap().as_str(), part.get(2).unwrap().as_str());
-        }
-
-        result.push(fields);
-    }
-
-    result
-}
-
-fn has_fields(passport: &HashMap<&str, &str>) -> bool {
+fn has_fields(passport: &Passport) -> bool {
     const FIELDS: [&str; 7] = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"];
 
-    all(&FIELDS, |f| passport.contains_key(f))
+    all(&FIELDS, |&f| passport.contains_key(f))
 }
 
-fn is_valid_passport(passport: &HashMap<&str, &str>) -> bool {
+fn is_valid_passport(passport: &Passport) -> bool {
     fn check_int(value: &str, min: usize, max: usize) -> bool {
         if let Ok(n) = value.parse::<usize>() {
             n >= min && n <= max
@@ -57,25 +39,49 @@ fn is_valid_passport(passport: &HashMap<&str, &str>) -> bool {
         ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"].contains(&value)
     }
 
-    has_fields(&passport)
-        && check_int(passport["byr"], 1920, 2002)
-        && check_int(passport["iyr"], 2010, 2020)
-        && check_int(passport["eyr"], 2020, 2030)
-        && check_height(passport["hgt"])
-        && check_regex(passport["hcl"], "^#[0-9a-f]{6}$")
-        && check_eyes(passport["ecl"])
-        && check_regex(passport["pid"], "^[0-9]{9}$")
+    has_fields(passport)
+        && check_int(&passport["byr"], 1920, 2002)
+        && check_int(&passport["iyr"], 2010, 2020)
+        && check_int(&passport["eyr"], 2020, 2030)
+        && check_height(&passport["hgt"])
+        && check_regex(&passport["hcl"], "^#[0-9a-f]{6}$")
+        && check_eyes(&passport["ecl"])
+        && check_regex(&passport["pid"], "^[0-9]{9}$")
 }
 
-pub fn run() -> Result {
-    let content = &read_input("day04")?.join("\n");
-    let passports = parse_input(&content);
+pub struct Day04;
+
+impl Solver for Day04 {
+    const DAY: usize = 4;
+    type Input = Vec<Passport>;
 
-    let count = passports.iter().filter(|p| has_fields(p)).count();
-    println!("part A: {}", count);
+    fn parse(input: &str) -> Result<Self::Input> {
+        lazy_static! {
+            static ref SPLIT_LINES: Regex = Regex::new("[ ]*\n[ ]*\n[ ]*").unwrap();
+            static ref FIELD: Regex = Regex::new("([^ \n:]*):([^ \n]*)").unwrap();
+        }
+
+        let mut result = vec![];
+        for chunk in SPLIT_LINES.split(input) {
+            let mut fields = HashMap::new();
 
-    let count = passports.iter().filter(|p| is_valid_passport(p)).count();
-    println!("part B: {}", count);
+            for part in FIELD.captures_iter(chunk) {
+                fields.insert(part[1].to_string(), part[2].to_string());
+            }
 
-    Ok(())
+            result.push(fields);
+        }
+
+        Ok(result)
+    }
+
+    fn part_a(passports: &Self::Input) -> Result<Answer> {
+        let count = passports.iter().filter(|p| has_fields(p)).count();
+        Ok(count.into())
+    }
+
+    fn part_b(passports: &Self::Input) -> Result<Answer> {
+        let count = passports.iter().filter(|p| is_valid_passport(p)).count();
+        Ok(count.into())
+    }
 }
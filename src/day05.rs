@@ -7,24 +7,31 @@ fn boarding_pass_to_number(string: &str) -> usize {
         .fold(0, |v, c| v * 2 + "RB".contains(c) as usize)
 }
 
-pub fn run() -> Result {
-    let passes = read_input("day05")?
-        .iter()
-        .map(|s| boarding_pass_to_number(s))
-        .collect::<BTreeSet<_>>();
+pub struct Day05;
 
-    let &lowest = passes.iter().min().unwrap();
-    let &highest = passes.iter().max().unwrap();
-    println!("part A: {}", highest);
+impl Solver for Day05 {
+    const DAY: usize = 5;
+    type Input = BTreeSet<usize>;
 
-    let missing = (lowest..=highest)
-        .filter(|i| !passes.contains(&i))
-        .next()
-        .unwrap();
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(input.lines().map(boarding_pass_to_number).collect())
+    }
+
+    fn part_a(passes: &Self::Input) -> Result<Answer> {
+        let &highest = passes.iter().max().context("no boarding passes")?;
 
-    println!("part B: {}", missing);
+        Ok(highest.into())
+    }
 
-    Ok(())
+    fn part_b(passes: &Self::Input) -> Result<Answer> {
+        let &lowest = passes.iter().min().context("no boarding passes")?;
+        let &highest = passes.iter().max().context("no boarding passes")?;
+        let missing = (lowest..=highest)
+            .find(|i| !passes.contains(i))
+            .context("no empty seat found")?;
+
+        Ok(missing.into())
+    }
 }
 
 #[cfg(test)]
@@ -1,17 +1,6 @@
 use crate::common::*;
 use std::collections::HashSet;
 
-fn parse_input(lines: &[String]) -> Vec<Vec<HashSet<char>>> {
-    lines
-        .iter()
-        .map(|line| line.chars().collect::<HashSet<_>>())
-        .group_by(|line| line.is_empty())
-        .into_iter()
-        .filter(|(empty, _)| !empty)
-        .map(|(_, lines)| lines.collect())
-        .collect()
-}
-
 fn union(answers: &[HashSet<char>]) -> HashSet<char> {
     answers
         .iter()
@@ -24,21 +13,37 @@ fn intersection(answers: &[HashSet<char>]) -> HashSet<char> {
     })
 }
 
-pub fn run() -> Result {
-    let groups = parse_input(&read_input("day06")?);
+pub struct Day06;
 
-    let sum: usize = groups.iter().map(|answers| union(answers).len()).sum();
+impl Solver for Day06 {
+    const DAY: usize = 6;
+    type Input = Vec<Vec<HashSet<char>>>;
 
-    println!("part A: {}", sum);
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(input
+            .lines()
+            .map(|line| line.chars().collect::<HashSet<_>>())
+            .group_by(|line| line.is_empty())
+            .into_iter()
+            .filter(|(empty, _)| !empty)
+            .map(|(_, lines)| lines.collect())
+            .collect())
+    }
 
-    let sum: usize = groups
-        .iter()
-        .map(|answers| intersection(answers).len())
-        .sum();
+    fn part_a(groups: &Self::Input) -> Result<Answer> {
+        let sum: usize = groups.iter().map(|answers| union(answers).len()).sum();
 
-    println!("part B: {}", sum);
+        Ok(sum.into())
+    }
+
+    fn part_b(groups: &Self::Input) -> Result<Answer> {
+        let sum: usize = groups
+            .iter()
+            .map(|answers| intersection(answers).len())
+            .sum();
 
-    Ok(())
+        Ok(sum.into())
+    }
 }
 
 #[cfg(test)]
@@ -50,11 +55,9 @@ mod test {
         let input = [
             "abc", "", "a", "b", "c", "", "ab", "ac", "", "a", "a", "a", "a", "", "b",
         ]
-        .iter()
-        .map(|v| v.to_string())
-        .collect::<Vec<_>>();
+        .join("\n");
 
-        let groups = parse_input(&input);
+        let groups = Day06::parse(&input).unwrap();
 
         assert_eq!(groups.len(), 5);
         assert_eq!(union(&groups[0]).len(), 3);
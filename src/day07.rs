@@ -53,22 +53,37 @@ fn count(source: NodeIndex, graph: &DiGraph<String, usize>) -> usize {
     total
 }
 
-pub fn run() -> Result {
-    let mut graph = DiGraph::new();
-    let mut nodes = HashMap::new();
+pub struct Day07;
 
-    for line in read_input("day07")? {
-        parse_edge(&line, &mut graph, &mut nodes)?;
+impl Solver for Day07 {
+    const DAY: usize = 7;
+    type Input = (DiGraph<String, usize>, HashMap<String, NodeIndex>);
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let mut graph = DiGraph::new();
+        let mut nodes = HashMap::new();
+
+        for line in input.lines() {
+            parse_edge(line, &mut graph, &mut nodes)?;
+        }
+
+        Ok((graph, nodes))
     }
 
-    let mut visited = HashSet::new();
-    bfs(nodes["shiny gold"], &graph, &mut visited);
-    println!("part A: {}", visited.len() - 1);
+    fn part_a((graph, nodes): &Self::Input) -> Result<Answer> {
+        let source = *nodes.get("shiny gold").context("no shiny gold bag")?;
 
-    let count = count(nodes["shiny gold"], &graph);
-    println!("part B: {}", count - 1);
+        let mut visited = HashSet::new();
+        bfs(source, graph, &mut visited);
+        Ok((visited.len() - 1).into())
+    }
 
-    Ok(())
+    fn part_b((graph, nodes): &Self::Input) -> Result<Answer> {
+        let source = *nodes.get("shiny gold").context("no shiny gold bag")?;
+
+        let count = count(source, graph);
+        Ok((count - 1).into())
+    }
 }
 
 #[cfg(test)]
@@ -1,47 +1,30 @@
 use crate::common::*;
+use crate::gbcode::repair::repair;
 use crate::gbcode::*;
-use std::collections::HashSet;
 
-fn run_until_done(program: &Program) -> Result<Process> {
-    let mut visited = HashSet::new();
-    let mut p = program.run();
+pub struct Day08;
 
-    while !p.done() && visited.insert(p.pc()) {
-        p.advance()?;
-    }
-
-    Ok(p)
-}
+impl Solver for Day08 {
+    const DAY: usize = 8;
+    type Input = Program;
 
-fn fix_program(program: &Program) -> Result<Program> {
-    use Instr::*;
-
-    for i in 0..program.len() {
-        let mut p = program.clone();
-
-        // Flip nop and jmp, skip others
-        match p[i] {
-            Nop(v) => p[i] = Jmp(v),
-            Jmp(v) => p[i] = Nop(v),
-            _ => continue,
-        }
+    fn parse(input: &str) -> Result<Self::Input> {
+        Program::parse(input)
+    }
 
-        if let Ok(result) = run_until_done(&p) {
-            if result.done() {
-                return Ok(p);
-            }
+    fn part_a(program: &Self::Input) -> Result<Answer> {
+        match program.run().run_to_halt_or_loop() {
+            Outcome::Looped { acc, .. } => Ok(acc.into()),
+            outcome => bail!("program did not loop: {:?}", outcome),
         }
     }
 
-    bail!("failed to fix program");
-}
-
-pub fn run() -> Result {
-    let p = Program::parse_input("day08")?;
-    println!("part A: {}", run_until_done(&p)?.acc());
+    fn part_b(program: &Self::Input) -> Result<Answer> {
+        let fixed = repair(program)?;
 
-    let fixed_p = fix_program(&p)?;
-    println!("part B: {}", run_until_done(&fixed_p)?.acc());
-
-    Ok(())
+        match fixed.run().run_to_halt_or_loop() {
+            Outcome::Terminated(acc) => Ok(acc.into()),
+            outcome => bail!("repaired program did not terminate: {:?}", outcome),
+        }
+    }
 }
@@ -34,19 +34,28 @@ fn find_range(numbers: &[usize], needle: usize) -> Result<(usize, usize)> {
     Err(anyhow!("range not found"))
 }
 
-pub fn run() -> Result {
-    let numbers = read_input("day09")?
-        .into_iter()
-        .map(|s| s.parse::<usize>())
-        .collect::<Result<Vec<_>, _>>()?;
+pub struct Day09;
 
-    let index = find_xmass_weakness(&numbers)?;
-    let p = numbers[index];
-    println!("part A: {}", p);
+impl Solver for Day09 {
+    const DAY: usize = 9;
+    type Input = Vec<usize>;
 
-    let (begin, end) = find_range(&numbers[..index], p)?;
-    let (min, max) = numbers[begin..=end].iter().minmax().into_option().unwrap();
-    println!("part B: {:?}", min + max);
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(input
+            .lines()
+            .map(|s| s.parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()?)
+    }
+
+    fn part_a(numbers: &Self::Input) -> Result<Answer> {
+        let index = find_xmass_weakness(numbers)?;
+        Ok(numbers[index].into())
+    }
 
-    Ok(())
+    fn part_b(numbers: &Self::Input) -> Result<Answer> {
+        let index = find_xmass_weakness(numbers)?;
+        let (begin, end) = find_range(&numbers[..index], numbers[index])?;
+        let (min, max) = numbers[begin..=end].iter().minmax().into_option().unwrap();
+        Ok((min + max).into())
+    }
 }
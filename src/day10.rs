@@ -28,20 +28,31 @@ fn find_combinations(adapters: &[i32]) -> u128 {
     result
 }
 
-pub fn run() -> Result {
-    let mut adapters = read_input("day10")?
-        .iter()
-        .map(|line| line.parse::<i32>())
-        .collect::<Result<Vec<_>, _>>()?;
-    adapters.sort();
+pub struct Day10;
 
-    let (diff1, diff3) = find_differences(&adapters);
-    println!("part A: {}", diff1 * diff3);
+impl Solver for Day10 {
+    const DAY: usize = 10;
+    type Input = Vec<i32>;
 
-    let combinations = find_combinations(&adapters);
-    println!("part B: {}", combinations);
+    fn parse(input: &str) -> Result<Self::Input> {
+        let mut adapters = input
+            .lines()
+            .map(|line| line.parse::<i32>())
+            .collect::<Result<Vec<_>, _>>()?;
+        adapters.sort();
 
-    Ok(())
+        Ok(adapters)
+    }
+
+    fn part_a(adapters: &Self::Input) -> Result<Answer> {
+        let (diff1, diff3) = find_differences(adapters);
+        Ok((diff1 * diff3).into())
+    }
+
+    fn part_b(adapters: &Self::Input) -> Result<Answer> {
+        let combinations = find_combinations(adapters);
+        Ok(combinations.into())
+    }
 }
 
 #[cfg(test)]
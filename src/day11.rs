@@ -1,135 +1,208 @@
+use crate::automaton::{adjacent, line_of_sight, Cell, Dense, Rule};
 use crate::common::*;
-use itertools::max;
-use ndarray::prelude::*;
-
-const DIRECTIONS: [[isize; 2]; 8] = [
-    [-1, -1],
-    [-1, 0],
-    [-1, 1],
-    [0, 1],
-    [0, -1],
-    [1, -1],
-    [1, 0],
-    [1, 1],
-];
-
-fn parse_input(lines: &[String]) -> Array2<char> {
-    let rows = lines.len();
-    let cols = max(map(lines, |l| l.len())).unwrap_or(0);
-    let mut grid = Array2::default((rows, cols));
-
-    for (i, line) in enumerate(lines) {
-        for (j, c) in enumerate(line.chars()) {
-            grid[[i, j]] = c;
+
+/// Which seats count as neighbors of a seat.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Visibility {
+    /// The up to eight adjacent seats.
+    Adjacent,
+    /// The first seat in each of the eight directions, looking past floor.
+    LineOfSight,
+}
+
+impl Visibility {
+    fn neighborhood(self) -> fn(&Grid<Cell>, [usize; 2]) -> Vec<[usize; 2]> {
+        match self {
+            Visibility::Adjacent => adjacent,
+            Visibility::LineOfSight => line_of_sight,
         }
     }
-
-    grid
 }
 
-fn neighbors(dims: [usize; 2], ij: [usize; 2]) -> impl Iterator<Item = [usize; 2]> {
-    DIRECTIONS.iter().filter_map(move |&[di, dj]| {
-        let [i, j] = ij;
-        let [rows, cols] = dims;
-        let [ni, nj] = [i as isize + di, j as isize + dj];
+/// The characters of a seat layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Alphabet {
+    pub occupied: char,
+    pub empty: char,
+    pub floor: char,
+}
 
-        if ni >= 0 && ni < rows as isize && nj >= 0 && nj < cols as isize {
-            return Some([ni as usize, nj as usize]);
+impl Default for Alphabet {
+    fn default() -> Self {
+        Self {
+            occupied: '#',
+            empty: 'L',
+            floor: '.',
         }
+    }
+}
 
-        None
-    })
+/// How people choose their seats: a seat fills up if no neighbor is
+/// occupied, and empties if at least `tolerance` neighbors are.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SeatRules {
+    pub visibility: Visibility,
+    pub tolerance: usize,
+    pub alphabet: Alphabet,
 }
 
-fn apply_round(old: &Array2<char>) -> Array2<char> {
-    let (rows, cols) = old.dim();
-    let mut new = Array2::default((rows, cols));
-
-    for i in 0..rows {
-        for j in 0..cols {
-            let occupied = neighbors([rows, cols], [i, j])
-                .filter(|&a| old[a] == '#')
-                .count();
-
-            new[[i, j]] = match old[[i, j]] {
-                'L' if occupied == 0 => '#',
-                '#' if occupied >= 4 => 'L',
-                c => c,
-            }
+/// The final layout and how many rounds changed something before it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Settled {
+    pub grid: Grid<Cell>,
+    pub rounds: usize,
+    pub occupied: usize,
+}
+
+impl SeatRules {
+    pub fn new(visibility: Visibility, tolerance: usize) -> Self {
+        Self {
+            visibility,
+            tolerance,
+            alphabet: Alphabet::default(),
         }
     }
 
-    new
-}
+    pub fn part_a() -> Self {
+        Self::new(Visibility::Adjacent, 4)
+    }
 
-fn far_neighbors(array: &Array2<char>, ij: [usize; 2]) -> impl Iterator<Item = [usize; 2]> + '_ {
-    DIRECTIONS.iter().filter_map(move |&[di, dj]| {
-        let [i, j] = ij;
-        let (rows, cols) = array.dim();
-        for delta in 1.. {
-            let [ni, nj] = [i as isize + di * delta, j as isize + dj * delta];
+    pub fn part_b() -> Self {
+        Self::new(Visibility::LineOfSight, 5)
+    }
 
-            if ni < 0 || ni >= rows as isize || nj < 0 || nj >= cols as isize {
-                break;
-            }
+    fn rule(&self) -> Rule {
+        Rule::new(&[0], &(0..self.tolerance).collect_vec())
+    }
 
-            if array[[ni as usize, nj as usize]] != '.' {
-                return Some([ni as usize, nj as usize]);
-            }
-        }
+    pub fn parse(&self, input: &str) -> Result<Grid<Cell>> {
+        let a = self.alphabet;
 
-        None
-    })
-}
+        Grid::parse_with(input, |c| match c {
+            c if c == a.occupied => Ok(Cell::Alive),
+            c if c == a.empty => Ok(Cell::Dead),
+            c if c == a.floor => Ok(Cell::Inert),
+            _ => bail!("invalid seat {:?}", c),
+        })
+    }
+
+    pub fn render(&self, grid: &Grid<Cell>) -> String {
+        grid.map(|&cell| match cell {
+            Cell::Alive => self.alphabet.occupied,
+            Cell::Dead => self.alphabet.empty,
+            Cell::Inert => self.alphabet.floor,
+        })
+        .to_string()
+    }
 
-fn apply_far_round(old: &Array2<char>) -> Array2<char> {
-    let (rows, cols) = old.dim();
-    let mut new = Array2::default((rows, cols));
-
-    for i in 0..rows {
-        for j in 0..cols {
-            let occupied = far_neighbors(old, [i, j])
-                .filter(|&a| old[a] == '#')
-                .count();
-
-            new[[i, j]] = match old[[i, j]] {
-                'L' if occupied == 0 => '#',
-                '#' if occupied >= 5 => 'L',
-                c => c,
-            }
+    /// The layout after every round, until it stops changing.
+    pub fn rounds(&self, grid: &Grid<Cell>) -> Rounds {
+        Rounds {
+            automaton: Dense::new(grid.clone(), self.visibility.neighborhood()),
+            rule: self.rule(),
+            done: false,
         }
     }
 
-    new
-}
+    pub fn settle(&self, grid: &Grid<Cell>) -> Settled {
+        let mut rounds = self.rounds(grid);
+        let count = rounds.by_ref().count();
+        let grid = rounds.automaton.cells().clone();
 
-fn count_occupied(grid: &Array2<char>) -> usize {
-    map(grid, |&c| (c == '#') as usize).sum()
+        Settled {
+            occupied: grid.count(|&c| c == Cell::Alive),
+            rounds: count,
+            grid,
+        }
+    }
 }
 
-fn repeat_until_convergence<T: Eq, F: Fn(&T) -> T>(val: &T, fun: F) -> T {
-    let mut new_val = (fun)(&val);
+/// Iterator returned by [`SeatRules::rounds`].
+#[derive(Clone, Debug)]
+pub struct Rounds {
+    automaton: Dense,
+    rule: Rule,
+    done: bool,
+}
 
-    loop {
-        let old_val = new_val;
-        new_val = (fun)(&old_val);
+impl Iterator for Rounds {
+    type Item = Grid<Cell>;
 
-        if old_val == new_val {
-            break old_val;
+    fn next(&mut self) -> Option<Grid<Cell>> {
+        if self.done || !self.automaton.step(&self.rule) {
+            self.done = true;
+            return None;
         }
+
+        Some(self.automaton.cells().clone())
     }
 }
 
-pub fn run() -> Result {
-    let grid = parse_input(&read_input("day11")?);
+pub struct Day11;
 
-    let new_grid = repeat_until_convergence(&grid, apply_round);
-    let count = count_occupied(&new_grid);
-    println!("part A: {}", count);
+impl Solver for Day11 {
+    const DAY: usize = 11;
+    type Input = Grid<Cell>;
 
-    let new_grid = repeat_until_convergence(&grid, apply_far_round);
-    let count = count_occupied(&new_grid);
-    println!("part B: {}", count);
+    fn parse(input: &str) -> Result<Self::Input> {
+        SeatRules::part_a().parse(input)
+    }
+
+    fn part_a(grid: &Self::Input) -> Result<Answer> {
+        Ok(SeatRules::part_a().settle(grid).occupied.into())
+    }
 
-    Ok(())
+    fn part_b(grid: &Self::Input) -> Result<Answer> {
+        Ok(SeatRules::part_b().settle(grid).occupied.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+L.LL.LL.LL
+LLLLLLL.LL
+L.L.L..L..
+LLLL.LL.LL
+L.LL.LL.LL
+L.LLLLL.LL
+..L.L.....
+LLLLLLLLLL
+L.LLLLLL.L
+L.LLLLL.LL";
+
+    #[test]
+    fn test_seats() {
+        let rules = SeatRules::part_a();
+        let grid = rules.parse(EXAMPLE).unwrap();
+        assert_eq!(rules.render(&grid), format!("{}\n", EXAMPLE));
+
+        let first = rules.rounds(&grid).next().unwrap();
+        assert_eq!(first.count(|&c| c == Cell::Alive), 71);
+
+        let settled = rules.settle(&grid);
+        assert_eq!((settled.rounds, settled.occupied), (5, 37));
+
+        let settled = SeatRules::part_b().settle(&grid);
+        assert_eq!((settled.rounds, settled.occupied), (6, 26));
+
+        // Nobody minds a full neighborhood, so everyone sits down at once.
+        let settled = SeatRules::new(Visibility::Adjacent, 9).settle(&grid);
+        assert_eq!((settled.rounds, settled.occupied), (1, 71));
+
+        let rules = SeatRules {
+            alphabet: Alphabet {
+                occupied: 'X',
+                empty: 'o',
+                floor: ' ',
+            },
+            ..SeatRules::part_a()
+        };
+        let grid = rules.parse("o o\nooX").unwrap();
+        assert_eq!(grid.count(|&c| c == Cell::Inert), 1);
+        assert_eq!(rules.render(&grid), "o o\nooX\n");
+        assert!(rules.parse("L.L").is_err());
+    }
 }
@@ -3,15 +3,23 @@ use crate::common::*;
 use recap::Recap;
 use serde::Deserialize;
 
-#[derive(Recap, Deserialize)]
-#[recap(regex = "(?P<c>.)(?P<v>[0-9]+)")]
-struct Instr {
-    c: char,
-    v: i32,
+/// The letter of a navigation instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum Command {
+    N,
+    S,
+    E,
+    W,
+    L,
+    R,
+    F,
 }
 
-fn parse_input(lines: &[String]) -> Result<Vec<Instr>> {
-    lines.iter().map(|line| Ok(line.parse()?)).collect()
+#[derive(Recap, Deserialize)]
+#[recap(regex = "^(?P<c>.)(?P<v>[0-9]+)$")]
+pub struct Instr {
+    c: Command,
+    v: i32,
 }
 
 fn rotate((x, y): (i32, i32), angle: i32) -> (i32, i32) {
@@ -30,17 +38,16 @@ fn execute(instr: &[Instr]) -> (i32, i32) {
 
     for &Instr { c, v } in instr {
         match c {
-            'N' => y += v,
-            'S' => y -= v,
-            'E' => x += v,
-            'W' => x -= v,
-            'R' => dir = rotate(dir, v),
-            'L' => dir = rotate(dir, -v),
-            'F' => {
+            Command::N => y += v,
+            Command::S => y -= v,
+            Command::E => x += v,
+            Command::W => x -= v,
+            Command::R => dir = rotate(dir, v),
+            Command::L => dir = rotate(dir, -v),
+            Command::F => {
                 x += dir.0 * v;
                 y += dir.1 * v;
             }
-            _ => println!("unknown command: {}", c),
         }
     }
 
@@ -53,34 +60,47 @@ fn execute_real(instr: &[Instr]) -> (i32, i32) {
 
     for &Instr { c, v } in instr {
         match c {
-            'E' => wp.0 += v,
-            'W' => wp.0 -= v,
-            'N' => wp.1 += v,
-            'S' => wp.1 -= v,
-            'R' => wp = rotate(wp, v),
-            'L' => wp = rotate(wp, -v),
-            'F' => {
+            Command::E => wp.0 += v,
+            Command::W => wp.0 -= v,
+            Command::N => wp.1 += v,
+            Command::S => wp.1 -= v,
+            Command::R => wp = rotate(wp, v),
+            Command::L => wp = rotate(wp, -v),
+            Command::F => {
                 x += wp.0 * v;
                 y += wp.1 * v;
             }
-            _ => println!("unknown command: {}", c),
         }
     }
 
     (x, y)
 }
 
-pub fn run() -> Result {
-    let input = read_input("day12")?;
-    let instr = parse_input(&input)?;
+pub struct Day12;
 
-    let (x, y) = execute(&instr);
-    println!("part A: ({}, {}) -> {}", x, y, x.abs() + y.abs());
+impl Solver for Day12 {
+    const DAY: usize = 12;
+    type Input = Vec<Instr>;
 
-    let (x, y) = execute_real(&instr);
-    println!("part B: ({}, {}) -> {}", x, y, x.abs() + y.abs());
+    fn parse(input: &str) -> Result<Self::Input> {
+        input
+            .lines()
+            .map(|line| {
+                line.parse()
+                    .with_context(|| format!("invalid line {:?}", line))
+            })
+            .collect()
+    }
 
-    Ok(())
+    fn part_a(instr: &Self::Input) -> Result<Answer> {
+        let (x, y) = execute(instr);
+        Ok((x.abs() + y.abs()).into())
+    }
+
+    fn part_b(instr: &Self::Input) -> Result<Answer> {
+        let (x, y) = execute_real(instr);
+        Ok((x.abs() + y.abs()).into())
+    }
 }
 
 #[cfg(test)]
@@ -89,17 +109,16 @@ mod test {
 
     #[test]
     fn test() {
-        let input = ["F10", "N3", "F7", "R90", "F11"]
-            .iter()
-            .copied()
-            .map(str::to_string)
-            .collect::<Vec<_>>();
-        let instr = parse_input(&input).unwrap();
+        let instr = Day12::parse("F10\nN3\nF7\nR90\nF11").unwrap();
 
         let (x, y) = execute(&instr);
         assert_eq!((x, y), (17, -8));
 
         let (x, y) = execute_real(&instr);
         assert_eq!((x, y), (214, -72));
+
+        assert!(Day12::parse("F10\nX3").is_err());
+        assert!("N3".parse::<Instr>().is_ok());
+        assert!("n3".parse::<Instr>().is_err());
     }
 }
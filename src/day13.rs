@@ -1,26 +1,20 @@
 use crate::common::*;
 
-fn parse_input(lines: &[String]) -> Result<(i32, Vec<(i32, i32)>)> {
-    let timestamp = lines[0].parse()?;
-    let busses = lines[1]
-        .split(',')
-        .enumerate()
-        .filter_map(|(i, x)| Some((i as i32, x.parse().ok()?)))
-        .collect();
-    Ok((timestamp, busses))
-}
-
-fn find_earliest_bus(timestamp: i32, busses: &[(i32, i32)]) -> (i32, i32) {
+/// Returns the first bus to depart after `timestamp` and how long to wait for
+/// it, or `None` if there are no busses.
+pub fn find_earliest_bus(timestamp: i32, busses: &[(i32, i32)]) -> Option<(i32, i32)> {
     busses
         .iter()
         .map(|&(_, b)| (b, (-timestamp).rem_euclid(b)))
         .min_by_key(|(_, t)| *t)
-        .unwrap()
 }
 
-fn find_earliest_time(busses: &[(i32, i32)]) -> i128 {
-    let mut result = 0 as i128;
-    let mut factor = 1 as i128;
+/// Finds the earliest time `t` such that every bus `(index, id)` departs at
+/// `t + index`, i.e., solves the system of congruences using the Chinese
+/// remainder theorem. Bus ids are assumed to be pairwise coprime.
+pub fn find_earliest_time(busses: &[(i32, i32)]) -> i128 {
+    let mut result = 0i128;
+    let mut factor = 1i128;
 
     for &(index, bus_id) in busses {
         let (bus_id, index) = (bus_id as i128, index as i128);
@@ -36,27 +30,52 @@ fn find_earliest_time(busses: &[(i32, i32)]) -> i128 {
     result
 }
 
-pub fn run() -> Result {
-    let (timestamp, busses) = parse_input(&read_input("day13")?)?;
-    let (bus_id, remaining) = find_earliest_bus(timestamp, &busses);
+pub struct Day13;
+
+impl Solver for Day13 {
+    const DAY: usize = 13;
+
+    /// The timestamp and the `(index, bus id)` pairs of the busses in service.
+    type Input = (i32, Vec<(i32, i32)>);
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let lines: Vec<_> = input.lines().collect();
+        if lines.len() < 2 {
+            bail!("expecting two lines");
+        }
 
-    println!(
-        "part A: {} * {} = {}",
-        bus_id,
-        remaining,
-        bus_id * remaining
-    );
+        let timestamp = lines[0].parse()?;
+        let busses = lines[1]
+            .split(',')
+            .enumerate()
+            .filter_map(|(i, x)| Some((i as i32, x.parse().ok()?)))
+            .collect();
+        Ok((timestamp, busses))
+    }
 
-    let t = find_earliest_time(&busses);
-    println!("part B: {}", t);
+    fn part_a((timestamp, busses): &Self::Input) -> Result<Answer> {
+        let (bus_id, remaining) =
+            find_earliest_bus(*timestamp, busses).context("no busses in service")?;
+        Ok((bus_id * remaining).into())
+    }
 
-    Ok(())
+    fn part_b((_, busses): &Self::Input) -> Result<Answer> {
+        let t = find_earliest_time(busses);
+        Ok(t.into())
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn test_earliest_bus() {
+        let busses = vec![(0, 7), (1, 13), (4, 59), (6, 31), (7, 19)];
+        assert_eq!(find_earliest_bus(939, &busses), Some((59, 5)));
+        assert_eq!(find_earliest_bus(939, &[]), None);
+    }
+
     #[test]
     fn test() {
         let input = vec![(0, 17), (2, 13), (3, 19)];
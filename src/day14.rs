@@ -5,45 +5,11 @@ use std::collections::HashMap;
 
 use Instr::*;
 #[derive(Debug, Clone, Copy)]
-enum Instr {
+pub enum Instr {
     Mask(u64, u64),
     Assign(u64, u64),
 }
 
-fn parse_input(lines: &[String]) -> Result<Vec<Instr>> {
-    #[derive(Recap, Deserialize)]
-    #[recap(regex = "mask = (?P<mask>[01X]+)")]
-    struct Mask {
-        mask: String,
-    };
-
-    #[derive(Recap, Deserialize)]
-    #[recap(regex = "mem\\[(?P<addr>[0-9]+)\\] = (?P<value>[0-9]+)")]
-    struct Assign {
-        addr: u64,
-        value: u64,
-    };
-
-    let mut output = vec![];
-    for line in lines {
-        if let Ok(Mask { mask }) = line.parse() {
-            // X,0 -> 0, 1 -> 1
-            let value = u64::from_str_radix(&mask.replace('X', "0"), 2).unwrap();
-
-            // 0,1 -> 0, X -> 1
-            let mask = u64::from_str_radix(&mask.replace('1', "0").replace('X', "1"), 2).unwrap();
-
-            output.push(Mask(mask, value));
-        } else if let Ok(Assign { addr, value }) = line.parse() {
-            output.push(Assign(addr, value));
-        } else {
-            bail!("failed to parse line: {}", line);
-        }
-    }
-
-    Ok(output)
-}
-
 fn execute_v1(instrs: &[Instr]) -> HashMap<u64, u64> {
     let mut mem = HashMap::new();
     let (mut mask, mut bits) = (0, 0);
@@ -100,18 +66,58 @@ fn execute_v2(instrs: &[Instr]) -> HashMap<u64, u64> {
     mem
 }
 
-pub fn run() -> Result {
-    let instrs = parse_input(&read_input("day14")?)?;
+pub struct Day14;
+
+impl Solver for Day14 {
+    const DAY: usize = 14;
+    type Input = Vec<Instr>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        #[derive(Recap, Deserialize)]
+        #[recap(regex = "mask = (?P<mask>[01X]+)")]
+        struct Mask {
+            mask: String,
+        }
+
+        #[derive(Recap, Deserialize)]
+        #[recap(regex = "mem\\[(?P<addr>[0-9]+)\\] = (?P<value>[0-9]+)")]
+        struct Assign {
+            addr: u64,
+            value: u64,
+        }
+
+        let mut output = vec![];
+        for line in input.lines() {
+            if let Ok(Mask { mask }) = line.parse() {
+                // X,0 -> 0, 1 -> 1
+                let value = u64::from_str_radix(&mask.replace('X', "0"), 2).unwrap();
+
+                // 0,1 -> 0, X -> 1
+                let mask =
+                    u64::from_str_radix(&mask.replace('1', "0").replace('X', "1"), 2).unwrap();
+
+                output.push(Mask(mask, value));
+            } else if let Ok(Assign { addr, value }) = line.parse() {
+                output.push(Assign(addr, value));
+            } else {
+                bail!("failed to parse line: {}", line);
+            }
+        }
 
-    let mem = execute_v1(&instrs);
-    let total = sum(mem.values().copied());
-    println!("part A: {:?}", total);
+        Ok(output)
+    }
 
-    let mem = execute_v2(&instrs);
-    let total = sum(mem.values().copied());
-    println!("part B: {:?}", total);
+    fn part_a(instrs: &Self::Input) -> Result<Answer> {
+        let mem = execute_v1(instrs);
+        let total = sum(mem.values().copied());
+        Ok(total.into())
+    }
 
-    Ok(())
+    fn part_b(instrs: &Self::Input) -> Result<Answer> {
+        let mem = execute_v2(instrs);
+        let total = sum(mem.values().copied());
+        Ok(total.into())
+    }
 }
 
 #[cfg(test)]
@@ -120,14 +126,15 @@ mod test {
 
     #[test]
     fn test() {
-        let program = vec![
-            "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X".to_string(),
-            "mem[8] = 11".to_string(),
-            "mem[7] = 101".to_string(),
-            "mem[8] = 0".to_string(),
-        ];
-
-        let program = parse_input(&program).unwrap();
+        let program = [
+            "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X",
+            "mem[8] = 11",
+            "mem[7] = 101",
+            "mem[8] = 0",
+        ]
+        .join("\n");
+
+        let program = Day14::parse(&program).unwrap();
         let _mem = execute_v1(&program);
     }
 }
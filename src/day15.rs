@@ -1,10 +1,6 @@
 use crate::common::*;
 use std::mem::replace;
 
-fn parse_input(line: &str) -> Result<Vec<usize>> {
-    line.split(',').map(|s| Ok(s.parse()?)).collect()
-}
-
 fn play_for_n_rounds(n: usize, nums: &[usize]) -> usize {
     if n < nums.len() {
         return nums[n];
@@ -23,16 +19,25 @@ fn play_for_n_rounds(n: usize, nums: &[usize]) -> usize {
     })
 }
 
-pub fn run() -> Result {
-    let nums = parse_input(&read_input("day15")?[0])?;
+pub struct Day15;
+
+impl Solver for Day15 {
+    const DAY: usize = 15;
+    type Input = Vec<usize>;
 
-    let result = play_for_n_rounds(2020 - 1, &nums);
-    println!("part A: {}", result);
+    fn parse(input: &str) -> Result<Self::Input> {
+        input.trim().split(',').map(|s| Ok(s.parse()?)).collect()
+    }
 
-    let result = play_for_n_rounds(30_000_000 - 1, &nums);
-    println!("part B: {}", result);
+    fn part_a(nums: &Self::Input) -> Result<Answer> {
+        let result = play_for_n_rounds(2020 - 1, nums);
+        Ok(result.into())
+    }
 
-    Ok(())
+    fn part_b(nums: &Self::Input) -> Result<Answer> {
+        let result = play_for_n_rounds(30_000_000 - 1, nums);
+        Ok(result.into())
+    }
 }
 
 #[cfg(test)]
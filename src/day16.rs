@@ -6,7 +6,7 @@ use serde::Deserialize;
 #[recap(
     regex = "^(?P<key>[a-zA-Z ]+): (?P<a_lo>[0-9]+)-(?P<a_hi>[0-9]+) or (?P<b_lo>[0-9]+)-(?P<b_hi>[0-9]+)$"
 )]
-struct Rule {
+pub struct Rule {
     key: String,
     a_lo: i32,
     a_hi: i32,
@@ -14,7 +14,7 @@ struct Rule {
     b_hi: i32,
 }
 
-type Ticket = Vec<i32>;
+pub type Ticket = Vec<i32>;
 
 impl Rule {
     fn is_valid(&self, v: i32) -> bool {
@@ -33,31 +33,6 @@ fn expect(line: Option<&str>, expected: &str) -> Result {
     }
 }
 
-fn parse_input(lines: &[String]) -> Result<(Vec<Rule>, Ticket, Vec<Ticket>)> {
-    let mut iter = lines.into_iter().map(|s| &**s);
-
-    let mut rules = vec![];
-    while let Some(line) = iter.next() {
-        if line.is_empty() {
-            break;
-        }
-
-        rules.push(line.parse()?);
-    }
-
-    expect(iter.next(), "your ticket:")?;
-    let my_ticket = parse_list(iter.next().unwrap_or_default())?;
-
-    expect(iter.next(), "")?;
-    expect(iter.next(), "nearby tickets:")?;
-    let mut tickets = vec![];
-    while let Some(line) = iter.next() {
-        tickets.push(parse_list(line)?);
-    }
-
-    Ok((rules, my_ticket, tickets))
-}
-
 fn delete_invalid_tickets(rules: &[Rule], tickets: &mut Vec<Ticket>) -> i32 {
     let mut error = 0;
 
@@ -111,19 +86,52 @@ fn reorder_fields(rules: &[Rule], tickets: &[Ticket]) -> Vec<Rule> {
     new_rules
 }
 
-pub fn run() -> Result {
-    let (mut rules, my_ticket, mut tickets) = parse_input(&read_input("day16")?)?;
+pub struct Day16;
+
+impl Solver for Day16 {
+    const DAY: usize = 16;
+    type Input = (Vec<Rule>, Ticket, Vec<Ticket>);
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let mut iter = input.lines();
 
-    let error_rate = delete_invalid_tickets(&rules, &mut tickets);
-    println!("part A: {}", error_rate);
+        let mut rules = vec![];
+        for line in &mut iter {
+            if line.is_empty() {
+                break;
+            }
+
+            rules.push(line.parse()?);
+        }
+
+        expect(iter.next(), "your ticket:")?;
+        let my_ticket = parse_list(iter.next().unwrap_or_default())?;
+
+        expect(iter.next(), "")?;
+        expect(iter.next(), "nearby tickets:")?;
+        let mut tickets = vec![];
+        for line in iter {
+            tickets.push(parse_list(line)?);
+        }
+
+        Ok((rules, my_ticket, tickets))
+    }
 
-    let rules = reorder_fields(&mut rules, &tickets);
-    let sum: usize = enumerate(rules)
-        .filter(|(_, rule)| rule.key.starts_with("departure"))
-        .map(|(i, _)| my_ticket[i] as usize)
-        .product();
+    fn part_a((rules, _, tickets): &Self::Input) -> Result<Answer> {
+        let error_rate = delete_invalid_tickets(rules, &mut tickets.clone());
+        Ok(error_rate.into())
+    }
+
+    fn part_b((rules, my_ticket, tickets): &Self::Input) -> Result<Answer> {
+        let mut tickets = tickets.clone();
+        delete_invalid_tickets(rules, &mut tickets);
 
-    println!("part B: {:?}", sum);
+        let rules = reorder_fields(rules, &tickets);
+        let sum: usize = enumerate(rules)
+            .filter(|(_, rule)| rule.key.starts_with("departure"))
+            .map(|(i, _)| my_ticket[i] as usize)
+            .product();
 
-    Ok(())
+        Ok(sum.into())
+    }
 }
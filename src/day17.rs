@@ -1,59 +1,86 @@
+use crate::automaton::{moore, Rule, Sparse};
 use crate::common::*;
-use itertools::iproduct;
 
-pub type Pos = [i32; 4];
+/// Runs six generations of Life in `N` dimensions, starting from the given
+/// slice at `z = 0` (and `w = 0`).
+fn simulate<const N: usize>(slice: &[[i32; 2]]) -> usize {
+    let cells = slice.iter().map(|&[x, y]| {
+        let mut pos = [0; N];
+        pos[0] = x;
+        pos[1] = y;
+        pos
+    });
 
-fn parse_input(lines: &[String]) -> HashSet<Pos> {
-    let mut result = HashSet::default();
-
-    for (y, line) in enumerate(lines) {
-        for (x, c) in enumerate(line.chars()) {
-            if c == '#' {
-                result.insert([x as i32, y as i32, 0, 0]);
-            }
-        }
+    let mut automaton = Sparse::new(cells, moore::<N>());
+    for _ in 0..6 {
+        automaton.step(&Rule::life());
     }
 
-    result
+    automaton.population()
 }
 
-fn simulate(pos: &HashSet<Pos>, dims: usize) -> HashSet<Pos> {
-    let mut count = HashMap::<Pos, usize>::default();
-    let neighbors: Vec<_> = iproduct!(-1..=1, -1..=1, -1..=1, -1..=1)
-        .filter(|&d| d != (0, 0, 0, 0))
-        .filter(|&d| dims > 3 || d.3 == 0)
-        .collect();
-
-    for [x, y, z, w] in pos {
-        for &(dx, dy, dz, dw) in &neighbors {
-            let p = [x + dx, y + dy, z + dz, w + dw];
-            *count.entry(p).or_default() += 1;
-        }
+/// The `z = 0` slice of every generation of the three-dimensional automaton,
+/// drawn with `#` for active cubes. All slices have the same size.
+pub fn slices(slice: &[[i32; 2]]) -> Vec<String> {
+    let cells = slice.iter().map(|&[x, y]| [x, y, 0]);
+    let mut automaton = Sparse::new(cells, moore::<3>());
+    let mut generations = vec![automaton.alive().clone()];
+
+    for _ in 0..6 {
+        automaton.step(&Rule::life());
+        generations.push(automaton.alive().clone());
     }
 
-    count
-        .into_iter()
-        .filter(|&(p, c)| c == 3 || (c == 2 && pos.contains(&p)))
-        .map(|(p, _)| p)
+    let visible = generations.iter().flatten().filter(|c| c[2] == 0);
+    let xs = visible.clone().map(|c| c[0]).minmax().into_option();
+    let ys = visible.map(|c| c[1]).minmax().into_option();
+    let ((x0, x1), (y0, y1)) = match (xs, ys) {
+        (Some(xs), Some(ys)) => (xs, ys),
+        _ => return vec![String::new(); generations.len()],
+    };
+
+    generations
+        .iter()
+        .map(|alive| {
+            (y0..=y1)
+                .map(|y| {
+                    (x0..=x1)
+                        .map(|x| if alive.contains(&[x, y, 0]) { '#' } else { '.' })
+                        .collect::<String>()
+                        + "\n"
+                })
+                .collect()
+        })
         .collect()
 }
 
-pub fn run() -> Result {
-    let pos = parse_input(&read_input("day17")?);
+pub struct Day17;
 
-    let mut new_pos = pos.clone();
-    for _ in 0..6 {
-        new_pos = simulate(&new_pos, 3);
-    }
+impl Solver for Day17 {
+    const DAY: usize = 17;
 
-    println!("part A: {}", new_pos.len());
+    /// The positions of the active cubes.
+    type Input = Vec<[i32; 2]>;
 
-    let mut new_pos = pos;
-    for _ in 0..6 {
-        new_pos = simulate(&new_pos, 4);
+    fn parse(input: &str) -> Result<Self::Input> {
+        let mut result = vec![];
+
+        for (y, line) in enumerate(input.lines()) {
+            for (x, c) in enumerate(line.chars()) {
+                if c == '#' {
+                    result.push([x as i32, y as i32]);
+                }
+            }
+        }
+
+        Ok(result)
     }
 
-    println!("part B: {}", new_pos.len());
+    fn part_a(slice: &Self::Input) -> Result<Answer> {
+        Ok(simulate::<3>(slice).into())
+    }
 
-    Ok(())
+    fn part_b(slice: &Self::Input) -> Result<Answer> {
+        Ok(simulate::<4>(slice).into())
+    }
 }
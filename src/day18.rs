@@ -69,22 +69,31 @@ fn evaluate_precedence(line: &str) -> Result<i64> {
     evaluate_generic(line, true)
 }
 
-pub fn run() -> Result {
-    let lines = read_input("day18")?;
+pub struct Day18;
 
-    let mut sum = 0;
-    for line in &lines {
-        sum += evaluate(line)?;
+impl Solver for Day18 {
+    const DAY: usize = 18;
+    type Input = Vec<String>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(input.lines().map(str::to_string).collect())
     }
-    println!("part A: {}", sum);
 
-    let mut sum = 0;
-    for line in &lines {
-        sum += evaluate_precedence(line)?;
+    fn part_a(lines: &Self::Input) -> Result<Answer> {
+        let mut sum = 0;
+        for line in lines {
+            sum += evaluate(line)?;
+        }
+        Ok(sum.into())
     }
-    println!("part B: {}", sum);
 
-    Ok(())
+    fn part_b(lines: &Self::Input) -> Result<Answer> {
+        let mut sum = 0;
+        for line in lines {
+            sum += evaluate_precedence(line)?;
+        }
+        Ok(sum.into())
+    }
 }
 
 #[cfg(test)]
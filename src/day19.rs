@@ -1,12 +1,15 @@
 use crate::common::*;
 use ndarray::prelude::*;
 
-enum Rule {
+/// A single alternative of a grammar rule, e.g. `8: 42 | 42 8` gives two rules.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Rule {
     Term(usize, char),
     Seq(usize, Vec<usize>),
 }
 
-fn parse_rule(line: &str, rules: &mut Vec<Rule>) -> Result {
+/// Parses a line such as `0: 4 1 5` or `4: "a"` and appends its rules to `rules`.
+pub fn parse_rule(line: &str, rules: &mut Vec<Rule>) -> Result {
     let mut parts = line.split(":");
     let index = parts
         .next()
@@ -36,27 +39,16 @@ fn parse_rule(line: &str, rules: &mut Vec<Rule>) -> Result {
     Ok(())
 }
 
-fn parse_input(lines: &[String]) -> Result<(Vec<Rule>, Vec<String>)> {
-    let index = lines.iter().position(|l| l.is_empty()).unwrap_or_default();
-    let preamble = lines[..index].to_vec();
-    let messages = lines[index..].to_vec();
-
-    let mut rules = vec![];
-    for line in preamble {
-        parse_rule(&line, &mut rules)?;
-    }
-
-    Ok((rules, messages))
-}
-
+/// Rules in Chomsky normal form, as used by [`matches()`].
 #[derive(Clone, Debug)]
-struct NormRules {
+pub struct NormRules {
     terms: Vec<(usize, char)>,           // R_i -> "c"
     triples: Vec<(usize, usize, usize)>, // R_i -> R_j R_k
     max_id: usize,
 }
 
-fn normalize_rules(input: &[Rule]) -> NormRules {
+/// Converts rules to Chomsky normal form. Sequences of at most three rules are supported.
+pub fn normalize_rules(input: &[Rule]) -> NormRules {
     let mut terms = vec![];
     let mut triples = vec![];
     let mut aliases = vec![];
@@ -119,7 +111,8 @@ fn normalize_rules(input: &[Rule]) -> NormRules {
     }
 }
 
-fn matches(line: &str, rules: &NormRules) -> bool {
+/// Checks whether rule 0 matches `line` using the CYK algorithm.
+pub fn matches(line: &str, rules: &NormRules) -> bool {
     if line.is_empty() {
         return false;
     }
@@ -156,37 +149,48 @@ fn matches(line: &str, rules: &NormRules) -> bool {
     m[[n - 1, 0, 0]]
 }
 
-pub fn run() -> Result {
-    let input = read_input("day19")?;
-    let (mut rules, msgs) = parse_input(&input)?;
+pub struct Day19;
 
-    let norm = normalize_rules(&rules);
-    let mut count = 0;
+impl Solver for Day19 {
+    const DAY: usize = 19;
 
-    for msg in &msgs {
-        let valid = matches(msg, &norm);
-        count += valid as usize;
+    /// The rules and the messages.
+    type Input = (Vec<Rule>, Vec<String>);
 
-        println!("{}: {}", msg, if valid { "yes" } else { "no" });
-    }
+    fn parse(input: &str) -> Result<Self::Input> {
+        let lines: Vec<_> = input.lines().collect();
+        let index = lines.iter().position(|l| l.is_empty()).unwrap_or_default();
+        let messages = map(&lines[index..], |s| s.to_string()).collect();
 
-    println!("part A: {}", count);
+        let mut rules = vec![];
+        for line in &lines[..index] {
+            parse_rule(line, &mut rules)?;
+        }
 
-    // Add some new rules
-    parse_rule("8: 42 8 | 42", &mut rules)?;
-    parse_rule("11: 42 11 31 | 42 31", &mut rules)?;
+        if rules.is_empty() {
+            bail!("no rules");
+        }
 
-    let norm = normalize_rules(&rules);
-    let mut count = 0;
+        Ok((rules, messages))
+    }
 
-    for msg in &msgs {
-        let valid = matches(msg, &norm);
-        count += valid as usize;
+    fn part_a((rules, msgs): &Self::Input) -> Result<Answer> {
+        let norm = normalize_rules(rules);
+        let count = msgs.iter().filter(|msg| matches(msg, &norm)).count();
 
-        println!("{}: {}", msg, if valid { "yes" } else { "no" });
+        Ok(count.into())
     }
 
-    println!("part B: {}", count);
+    fn part_b((rules, msgs): &Self::Input) -> Result<Answer> {
+        let mut rules = rules.clone();
 
-    Ok(())
+        // Add some new rules
+        parse_rule("8: 42 8 | 42", &mut rules)?;
+        parse_rule("11: 42 11 31 | 42 31", &mut rules)?;
+
+        let norm = normalize_rules(&rules);
+        let count = msgs.iter().filter(|msg| matches(msg, &norm)).count();
+
+        Ok(count.into())
+    }
 }
@@ -1,37 +1,8 @@
 use crate::common::*;
+use crate::pattern::{self, Template};
 use enum_map::{Enum, EnumMap};
 use ndarray::prelude::*;
-
-fn parse_input(lines: &[String]) -> Result<(Vec<usize>, Vec<Array2<char>>)> {
-    let mut ids = vec![];
-    let mut maps = vec![];
-    let mut lines = lines.iter().map(|s| &**s);
-
-    while let Some(line) = lines.next() {
-        let index = find("^Tile ([0-9]+):$", line)
-            .and_then(|c| c[1].parse::<usize>().ok())
-            .ok_or(anyhow!("invalid line: {:?}", line))?;
-
-        let mut map = vec![];
-        for _ in 0..10 {
-            let line = lines.next().unwrap_or_default();
-
-            if line.len() != 10 {
-                bail!("invalid line: {:?}", line);
-            }
-
-            map.extend(line.chars());
-        }
-
-        let map = Array::from(map).into_shape((10, 10))?;
-
-        ids.push(index);
-        maps.push(map);
-        lines.next();
-    }
-
-    Ok((ids, maps))
-}
+use std::iter::successors;
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Enum)]
 enum Side {
@@ -42,6 +13,8 @@ enum Side {
 }
 
 impl Side {
+    const ALL: [Side; 4] = [Side::Top, Side::Right, Side::Bottom, Side::Left];
+
     fn opposite(self) -> Self {
         use Side::*;
         match self {
@@ -53,41 +26,129 @@ impl Side {
     }
 }
 
-fn variations<'a>(array: ArrayView2<'a, char>) -> impl Iterator<Item = ArrayView2<'a, char>> {
-    (0..8).map(move |i| {
-        let mut m = array;
+/// The number of rows and columns of the square tiles, which must all have the
+/// same size.
+fn tile_size(ids: &[usize], maps: &[Array2<char>]) -> Result<usize> {
+    if ids.len() != maps.len() {
+        bail!("found {} ids for {} tiles", ids.len(), maps.len());
+    }
 
-        if i & 0x1 == 0 {
-            m = m.reversed_axes()
-        }
+    let size = maps.first().map(|m| m.nrows()).context("no tiles")?;
+
+    if size < 3 {
+        bail!("tiles must be at least 3 by 3, found {}", size);
+    }
+
+    if let Some(i) = (0..maps.len()).find(|&i| maps[i].dim() != (size, size)) {
+        bail!(
+            "tile {} is {:?}, expecting {} by {} like the first tile",
+            ids[i],
+            maps[i].dim(),
+            size,
+            size
+        );
+    }
+
+    Ok(size)
+}
+
+/// The edge on `side`, where `#` is true, read from left to right or from top
+/// to bottom.
+fn edge(map: ArrayView2<char>, side: Side) -> Vec<bool> {
+    let last = map.nrows() - 1;
+    let cells = match side {
+        Side::Top => map.row(0),
+        Side::Bottom => map.row(last),
+        Side::Left => map.column(0),
+        Side::Right => map.column(last),
+    };
+
+    cells.iter().map(|&c| c == '#').collect()
+}
+
+/// A key for `edge` that does not depend on the direction it is read in.
+fn signature(edge: &[bool]) -> Vec<bool> {
+    let reversed = edge.iter().rev().copied().collect_vec();
+    reversed.min(edge.to_vec())
+}
 
-        if i & 0x2 == 0 {
-            m.invert_axis(Axis(0));
+/// Finds the tiles that share an edge, in any orientation, by indexing the
+/// edges by a signature that is the same when read in either direction.
+struct EdgeIndex {
+    tiles: HashMap<Vec<bool>, Vec<usize>>,
+}
+
+impl EdgeIndex {
+    fn new(ids: &[usize], maps: &[Array2<char>]) -> Result<Self> {
+        tile_size(ids, maps)?;
+        let mut index = Self { tiles: default() };
+
+        for (i, map) in enumerate(maps) {
+            for &side in &Side::ALL {
+                let tiles = index
+                    .tiles
+                    .entry(signature(&edge(map.view(), side)))
+                    .or_default();
+
+                if tiles.last() != Some(&i) {
+                    tiles.push(i);
+                }
+            }
         }
 
-        if i & 0x4 == 0 {
-            m.invert_axis(Axis(1));
+        if let Some(tiles) = index.tiles.values().find(|tiles| tiles.len() > 2) {
+            bail!("{} tiles share the same edge", tiles.len());
         }
 
-        m
-    })
+        Ok(index)
+    }
+
+    /// The other tile with the given edge, if any.
+    fn matching(&self, tile: usize, edge: &[bool]) -> Option<usize> {
+        self.tiles[&signature(edge)]
+            .iter()
+            .copied()
+            .find(|&j| j != tile)
+    }
+
+    /// The tiles with two adjacent sides that match no other tile.
+    fn corners(&self, maps: &[Array2<char>]) -> Vec<usize> {
+        use Side::*;
+
+        (0..maps.len())
+            .filter(|&i| {
+                let open = |side| self.matching(i, &edge(maps[i].view(), side)).is_none();
+                (open(Top) || open(Bottom)) && (open(Left) || open(Right))
+            })
+            .collect()
+    }
+}
+
+/// Where every tile goes in the assembled image, and how it is rotated or
+/// flipped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Layout {
+    /// The index of the tile at every position.
+    pub grid: Array2<usize>,
+    /// How every tile is rotated or flipped to fit, by index.
+    pub transforms: Vec<Transform>,
 }
 
-fn rearrange_maps(maps: &mut [Array2<char>]) -> Array2<usize> {
+/// Finds how the tiles with the given ids fit together. The first tile keeps
+/// its orientation.
+pub fn rearrange_maps(ids: &[usize], maps: &[Array2<char>]) -> Result<Layout> {
     use Side::*;
 
+    let index = EdgeIndex::new(ids, maps)?;
     let n = maps.len();
-    let mut neighbors = Vec::<EnumMap<_, Option<usize>>>::new();
-    let mut remaining: HashSet<_> = (0..n).collect();
-    let mut queue = vec![];
+    let mut neighbors = vec![EnumMap::<_, Option<usize>>::default(); n];
+    let mut transforms = vec![None; n];
+    let mut queue = vec![0];
 
-    neighbors.resize(n, default());
-    queue.push(0);
+    transforms[0] = Some(Transform::Identity);
 
     while let Some(i) = queue.pop() {
-        if !remaining.remove(&i) {
-            continue;
-        }
+        let a = transforms[i].unwrap().apply(maps[i].view());
 
         for &side in &[Top, Right, Bottom, Left] {
             // Side already found by a neighbor.
@@ -95,151 +156,306 @@ fn rearrange_maps(maps: &mut [Array2<char>]) -> Array2<usize> {
                 continue;
             }
 
-            let a = maps[i].view();
-            let mut result = None;
-
-            'a: for &j in &remaining {
-                for b in variations(maps[j].view()) {
-                    let matches = match side {
-                        Top => a.row(0) == b.row(9),
-                        Right => a.column(9) == b.column(0),
-                        Bottom => a.row(9) == b.row(0),
-                        Left => a.column(0) == b.column(9),
-                    };
-
-                    if matches {
-                        result = Some((j, b.to_owned()));
-                        break 'a;
-                    }
+            let border = edge(a, side);
+            let j = match index.matching(i, &border) {
+                Some(j) => j,
+                None => continue,
+            };
+
+            let fits = |t: Transform| edge(t.apply(maps[j].view()), side.opposite()) == border;
+
+            match transforms[j] {
+                None => {
+                    let t = Transform::ALL
+                        .iter()
+                        .copied()
+                        .find(|&t| fits(t))
+                        .context("matching edge not found")?;
+                    transforms[j] = Some(t);
+                    queue.push(j);
+                }
+                Some(t) if !fits(t) => {
+                    bail!(
+                        "tiles {} and {} share an edge but do not fit",
+                        ids[i],
+                        ids[j]
+                    );
                 }
+                Some(_) => {}
             }
 
-            if let Some((j, m)) = result {
-                maps[j] = m;
-                neighbors[i][side] = Some(j);
-                neighbors[j][side.opposite()] = Some(i);
-                queue.push(j);
-            }
+            neighbors[i][side] = Some(j);
+            neighbors[j][side.opposite()] = Some(i);
         }
     }
 
-    let mut grid = Array2::<usize>::zeros((12, 12));
-    let mut index = (0..n)
-        .filter(|&i| neighbors[i][Top] == None && neighbors[i][Left] == None)
-        .next()
-        .unwrap();
-
-    // Fill first column
-    grid[[0, 0]] = index;
-    for i in 0..11 {
-        index = neighbors[index][Bottom].unwrap();
-        grid[[i + 1, 0]] = index;
+    let remaining = transforms.iter().filter(|t| t.is_none()).count();
+    if remaining > 0 {
+        bail!("{} tiles do not fit next to the others", remaining);
     }
 
-    // Fill each row
-    for i in 0..12 {
-        let mut index = grid[[i, 0]];
+    let corner = (0..n)
+        .filter(|&i| neighbors[i][Top].is_none() && neighbors[i][Left].is_none())
+        .exactly_one()
+        .map_err(|corners| anyhow!("expecting 1 top left corner, found {}", corners.count()))?;
 
-        for j in 0..11 {
-            index = neighbors[index][Right].unwrap();
-            grid[[i, j + 1]] = index;
-        }
+    let neighbors = &neighbors;
+    let walk = |start: usize, side: Side| {
+        successors(Some(start), move |&i| neighbors[i][side]).take(n + 1)
+    };
+
+    let rows = walk(corner, Bottom)
+        .map(|first| walk(first, Right).collect_vec())
+        .take(n + 1)
+        .collect_vec();
+    let cols = rows[0].len();
+
+    if rows.iter().any(|row| row.len() != cols) || rows.len() * cols != n {
+        bail!("tiles do not form a rectangle");
     }
 
-    grid
+    Ok(Layout {
+        grid: Array2::from_shape_vec((rows.len(), cols), rows.concat())?,
+        transforms: transforms.into_iter().flatten().collect(),
+    })
 }
 
-fn reassemble_maps(maps: &[Array2<char>], grid: ArrayView2<usize>) -> Array2<char> {
-    let mut result = Array2::<char>::from_elem((96, 96), '0');
+/// Joins the tiles as given by `layout`, without their borders.
+pub fn reassemble_maps(
+    ids: &[usize],
+    maps: &[Array2<char>],
+    layout: &Layout,
+) -> Result<Array2<char>> {
+    let inner = tile_size(ids, maps)? - 2;
+    if layout.transforms.len() != maps.len() {
+        bail!(
+            "layout has {} transforms for {} tiles",
+            layout.transforms.len(),
+            maps.len()
+        );
+    }
+
+    let (rows, cols) = layout.grid.dim();
+    let mut result = Array2::<char>::from_elem((rows * inner, cols * inner), '0');
 
-    for i in 0..12 {
-        for j in 0..12 {
-            let map = &maps[grid[[i, j]]];
+    for ((i, j), &index) in layout.grid.indexed_iter() {
+        let map = maps.get(index).context("tile index out of range")?;
+        let map = layout.transforms[index].apply(map.view());
+        let borderless = map.slice(s![1..=inner, 1..=inner]);
 
-            let borderless = map.slice(s![1..9, 1..9]);
-            result
-                .slice_mut(s![8 * i..8 * (i + 1), 8 * j..8 * (j + 1)])
-                .assign(&borderless);
-        }
+        result
+            .slice_mut(s![inner * i..inner * (i + 1), inner * j..inner * (j + 1)])
+            .assign(&borderless);
     }
 
-    result
+    Ok(result)
 }
 
-fn find_sea_monsters(map: ArrayView2<char>) -> Array2<char> {
-    let drawing = [
-        "                  # ",
-        "#    ##    ##    ###",
-        " #  #  #  #  #  #   ",
-    ];
+const SEA_MONSTER: &str = "
+                  #
+#    ##    ##    ###
+ #  #  #  #  #  #";
 
-    let mut spots = vec![];
-    let mut output = map.to_owned();
+/// The assembled image with the sea monsters drawn as `O`.
+pub fn sea_monster_map(ids: &[usize], maps: &[Array2<char>]) -> Result<Array2<char>> {
+    let layout = rearrange_maps(ids, maps)?;
+    let mut map = reassemble_maps(ids, maps, &layout)?;
 
-    for (di, line) in enumerate(&drawing) {
-        for (dj, c) in enumerate(line.chars()) {
-            if c == '#' {
-                spots.push((di, dj));
-            }
-        }
+    let monster = Template::parse(SEA_MONSTER)?;
+    let search = pattern::search(map.view(), &[monster]);
+    if search.matches.is_empty() {
+        bail!("no sea monsters found");
+    }
+
+    for &p in search.matches.iter().flat_map(|m| &m.cells) {
+        map[p] = 'O';
     }
 
-    for ((i, j), _) in map.indexed_iter() {
-        let mut valid = true;
+    Ok(map)
+}
 
-        for &(di, dj) in &spots {
-            valid &= map.get([i + di, j + dj]) == Some(&'#');
-        }
+pub struct Day20;
 
-        if !valid {
-            continue;
-        }
+impl Solver for Day20 {
+    const DAY: usize = 20;
+
+    /// The ids of the tiles and their contents.
+    type Input = (Vec<usize>, Vec<Array2<char>>);
 
-        for &(di, dj) in &spots {
-            output[[i + di, j + dj]] = 'O';
+    fn parse(input: &str) -> Result<Self::Input> {
+        let mut ids = vec![];
+        let mut maps = vec![];
+        let mut lines = input.lines();
+
+        while let Some(line) = lines.next() {
+            let index = find("^Tile ([0-9]+):$", line)
+                .and_then(|c| c[1].parse::<usize>().ok())
+                .ok_or(anyhow!("invalid line: {:?}", line))?;
+
+            let tile = lines.by_ref().take_while(|l| !l.is_empty()).join("\n");
+            let map = Grid::parse(&tile)
+                .with_context(|| format!("invalid tile {}", index))?
+                .into_array();
+
+            ids.push(index);
+            maps.push(map);
         }
-    }
 
-    output
-}
+        tile_size(&ids, &maps)?;
+        Ok((ids, maps))
+    }
 
-fn print_map(map: ArrayView2<char>) {
-    for row in map.genrows() {
-        for c in row {
-            print!("{}", c);
+    fn part_a((ids, maps): &Self::Input) -> Result<Answer> {
+        let corners = EdgeIndex::new(ids, maps)?.corners(maps);
+        if corners.is_empty() {
+            bail!("no corner tiles found");
         }
-        println!();
+
+        let corners: usize = corners.iter().map(|&i| ids[i]).product();
+        Ok(corners.into())
     }
-}
 
-pub fn run() -> Result {
-    let (ids, mut maps) = parse_input(&read_input("day20")?)?;
+    fn part_b((ids, maps): &Self::Input) -> Result<Answer> {
+        let map = sea_monster_map(ids, maps)?;
+        let count = map.iter().filter(|&&c| c == '#').count();
+        Ok(count.into())
+    }
+}
 
-    let grid = rearrange_maps(&mut maps);
-    println!(
-        "part A: {}",
-        ids[grid[[0, 0]]] * ids[grid[[11, 0]]] * ids[grid[[0, 11]]] * ids[grid[[11, 11]]]
-    );
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Cuts a random image into `rows` by `cols` tiles of the given size,
+    /// where neighboring tiles share their borders, and scrambles them. Also
+    /// returns how every tile was rotated or flipped.
+    fn puzzle(rows: usize, cols: usize, size: usize) -> (Vec<Array2<char>>, Vec<Transform>) {
+        let mut seed = 0x2545_f491_u64;
+        let mut random = move || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (seed >> 33) as usize
+        };
+
+        let step = size - 1;
+        let image = Array2::from_shape_simple_fn((rows * step + 1, cols * step + 1), || {
+            if random() % 2 == 0 {
+                '#'
+            } else {
+                '.'
+            }
+        });
+
+        let mut tiles = vec![];
+        for i in 0..rows {
+            for j in 0..cols {
+                let tile = image.slice(s![i * step..i * step + size, j * step..j * step + size]);
+                let t = Transform::ALL[random() % 8];
+                tiles.push((t.apply(tile).to_owned(), t));
+            }
+        }
 
-    let result = reassemble_maps(&maps, grid.view());
+        for i in (1..tiles.len()).rev() {
+            tiles.swap(i, random() % (i + 1));
+        }
 
-    println!("result:");
-    print_map(result.view());
-    println!();
+        tiles.into_iter().unzip()
+    }
 
-    for m in variations(result.view()) {
-        let result = find_sea_monsters(m);
+    fn ids(maps: &[Array2<char>]) -> Vec<usize> {
+        (1001..).take(maps.len()).collect()
+    }
 
-        if all(&result, |&c| c != 'O') {
-            continue;
+    #[test]
+    fn test_assemble() {
+        let (maps, scrambled) = puzzle(2, 3, 16);
+        let layout = rearrange_maps(&ids(&maps), &maps).unwrap();
+        let grid = &layout.grid;
+        let (rows, cols) = grid.dim();
+        assert_eq!(rows * cols, 6);
+        assert!(rows == 2 || rows == 3);
+
+        // Every tile is turned back, up to a symmetry of the whole image.
+        let whole = scrambled[0].then(layout.transforms[0]);
+        for (&s, &t) in scrambled.iter().zip(&layout.transforms) {
+            assert_eq!(s.then(t), whole);
         }
 
-        let count = result.iter().filter(|&&c| c == '#').count();
+        let tile = |index: usize| layout.transforms[index].apply(maps[index].view());
+        for ((i, j), &index) in grid.indexed_iter() {
+            if j + 1 < cols {
+                assert_eq!(tile(index).column(15), tile(grid[[i, j + 1]]).column(0));
+            }
+            if i + 1 < rows {
+                assert_eq!(tile(index).row(15), tile(grid[[i + 1, j]]).row(0));
+            }
+        }
 
-        println!("part B: {}", count);
-        print_map(result.view());
-        println!();
+        let corners = EdgeIndex::new(&ids(&maps), &maps).unwrap().corners(&maps);
+        let expected = [[0, 0], [0, cols - 1], [rows - 1, 0], [rows - 1, cols - 1]];
+        assert_eq!(
+            corners.iter().sorted().collect_vec(),
+            expected.iter().map(|&p| &grid[p]).sorted().collect_vec()
+        );
+
+        let image = reassemble_maps(&ids(&maps), &maps, &layout).unwrap();
+        assert_eq!(image.dim(), (rows * 14, cols * 14));
+
+        let mut broken = layout.clone();
+        broken.transforms.pop();
+        assert!(reassemble_maps(&ids(&maps), &maps, &broken).is_err());
+        broken.grid[[0, 0]] = 6;
+        broken.transforms.push(Transform::Identity);
+        assert!(reassemble_maps(&ids(&maps), &maps, &broken).is_err());
+
+        // A tile given a quarter turn is turned back by its transform.
+        assert_eq!(layout.transforms[0], Transform::Identity);
+        let mut turned = maps.clone();
+        turned[3] = Transform::Rot90.apply(maps[3].view()).to_owned();
+        let again = rearrange_maps(&ids(&turned), &turned).unwrap();
+        assert_eq!(again.grid, layout.grid);
+        assert_eq!(
+            again.transforms[3],
+            Transform::Rot270.then(layout.transforms[3])
+        );
+
+        let (maps, _) = puzzle(1, 1, 5);
+        assert_eq!(
+            rearrange_maps(&ids(&maps), &maps).unwrap().grid.dim(),
+            (1, 1)
+        );
+
+        let (mut maps, _) = puzzle(2, 2, 12);
+        maps.extend(puzzle(1, 1, 12).0);
+        assert!(rearrange_maps(&ids(&maps), &maps).is_err());
+
+        let (mut maps, _) = puzzle(2, 2, 12);
+        maps.push(Array2::from_elem((5, 5), '.'));
+        let error = rearrange_maps(&ids(&maps), &maps).unwrap_err();
+        assert!(error.to_string().starts_with("tile 1005 is (5, 5)"));
+        assert!(rearrange_maps(&[1], &maps).is_err());
+        assert!(rearrange_maps(&[], &[]).is_err());
     }
 
-    Ok(())
+    #[test]
+    fn test_large() {
+        let (maps, _) = puzzle(40, 30, 24);
+        let layout = rearrange_maps(&ids(&maps), &maps).unwrap();
+        assert_eq!(layout.grid.len(), 1200);
+        assert_eq!(
+            EdgeIndex::new(&ids(&maps), &maps)
+                .unwrap()
+                .corners(&maps)
+                .len(),
+            4
+        );
+
+        // Edges longer than a machine word.
+        let (maps, _) = puzzle(3, 2, 100);
+        let layout = rearrange_maps(&ids(&maps), &maps).unwrap();
+        assert_eq!(layout.grid.len(), 6);
+        assert_eq!(
+            reassemble_maps(&ids(&maps), &maps, &layout).unwrap().len(),
+            6 * 98 * 98
+        );
+    }
 }
@@ -2,7 +2,7 @@ use crate::common::*;
 use std::str::FromStr;
 
 #[derive(Debug)]
-struct Food {
+pub struct Food {
     ingredients: Vec<String>,
     allergens: Vec<String>,
 }
@@ -21,10 +21,6 @@ impl FromStr for Food {
     }
 }
 
-fn parse_input(lines: &[String]) -> Result<Vec<Food>> {
-    map(lines, |s| s.parse()).collect()
-}
-
 fn find_allergens(foods: &[Food]) -> Result<HashMap<&str, &str>> {
     let mut output: HashMap<&str, &str> = default();
     let mut options: HashMap<&str, HashSet<&str>> = default();
@@ -58,29 +54,40 @@ fn find_allergens(foods: &[Food]) -> Result<HashMap<&str, &str>> {
     Ok(output)
 }
 
-pub fn run() -> Result {
-    let foods = parse_input(&read_input("day21")?)?;
+pub struct Day21;
+
+impl Solver for Day21 {
+    const DAY: usize = 21;
+    type Input = Vec<Food>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        input.lines().map(str::parse).collect()
+    }
 
-    let ing2all = find_allergens(&foods)?;
+    fn part_a(foods: &Self::Input) -> Result<Answer> {
+        let ing2all = find_allergens(foods)?;
 
-    let mut count = 0;
-    for food in &foods {
-        for ing in &food.ingredients {
-            if !ing2all.contains_key(&**ing) {
-                count += 1;
+        let mut count = 0;
+        for food in foods {
+            for ing in &food.ingredients {
+                if !ing2all.contains_key(&**ing) {
+                    count += 1;
+                }
             }
         }
-    }
 
-    println!("part A: {}", count);
+        Ok(count.into())
+    }
 
-    let list = ing2all
-        .iter()
-        .sorted_by(|(_, a1), (_, a2)| a1.cmp(a2))
-        .map(|(ing, _)| ing)
-        .join(",");
+    fn part_b(foods: &Self::Input) -> Result<Answer> {
+        let ing2all = find_allergens(foods)?;
 
-    println!("pat B: {}", list);
+        let list = ing2all
+            .iter()
+            .sorted_by(|(_, a1), (_, a2)| a1.cmp(a2))
+            .map(|(ing, _)| ing)
+            .join(",");
 
-    Ok(())
+        Ok(list.into())
+    }
 }
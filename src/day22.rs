@@ -1,30 +1,6 @@
 use crate::common::*;
 use std::collections::VecDeque;
 
-fn parse_input(lines: &[String]) -> Result<(Vec<usize>, Vec<usize>)> {
-    let mut iter = lines.iter().map(|s| &**s);
-    let mut player1 = vec![];
-    let mut player2 = vec![];
-
-    if iter.next() != Some("Player 1:") {
-        bail!("invalid input");
-    }
-
-    while let Ok(i) = iter.next().unwrap_or_default().parse() {
-        player1.push(i);
-    }
-
-    if iter.next() != Some("Player 2:") {
-        bail!("invalid input");
-    }
-
-    while let Ok(i) = iter.next().unwrap_or_default().parse() {
-        player2.push(i);
-    }
-
-    Ok((player1, player2))
-}
-
 fn score(cards: &[usize]) -> usize {
     cards
         .iter()
@@ -82,16 +58,47 @@ fn play_game(cards1: &[usize], cards2: &[usize], recursive: bool) -> (usize, Vec
     }
 }
 
-pub fn run() -> Result {
-    let (cards1, cards2) = parse_input(&read_input("day22")?)?;
+pub struct Day22;
+
+impl Solver for Day22 {
+    const DAY: usize = 22;
 
-    let (_winner, cards) = play_game(&cards1, &cards2, false);
-    println!("part A: {}", score(&cards));
+    /// The decks of both players.
+    type Input = (Vec<usize>, Vec<usize>);
 
-    let (_winner, cards) = play_game(&cards1, &cards2, true);
-    println!("part B: {}", score(&cards));
+    fn parse(input: &str) -> Result<Self::Input> {
+        let mut iter = input.lines();
+        let mut player1 = vec![];
+        let mut player2 = vec![];
 
-    Ok(())
+        if iter.next() != Some("Player 1:") {
+            bail!("invalid input");
+        }
+
+        while let Ok(i) = iter.next().unwrap_or_default().parse() {
+            player1.push(i);
+        }
+
+        if iter.next() != Some("Player 2:") {
+            bail!("invalid input");
+        }
+
+        while let Ok(i) = iter.next().unwrap_or_default().parse() {
+            player2.push(i);
+        }
+
+        Ok((player1, player2))
+    }
+
+    fn part_a((cards1, cards2): &Self::Input) -> Result<Answer> {
+        let (_winner, cards) = play_game(cards1, cards2, false);
+        Ok(score(&cards).into())
+    }
+
+    fn part_b((cards1, cards2): &Self::Input) -> Result<Answer> {
+        let (_winner, cards) = play_game(cards1, cards2, true);
+        Ok(score(&cards).into())
+    }
 }
 
 #[cfg(test)]
@@ -39,23 +39,39 @@ fn play_game(mut cups: Vec<usize>, rounds: usize) -> Vec<usize> {
     cups
 }
 
-pub fn run() -> Result {
-    let mut cups = vec![];
-    for c in read_input("day23")?[0].chars() {
-        cups.push(c.to_digit(10).unwrap() as _);
-    }
+pub struct Day23;
+
+impl Solver for Day23 {
+    const DAY: usize = 23;
+    type Input = Vec<usize>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let mut cups = vec![];
+        for c in input.trim().chars() {
+            cups.push(c.to_digit(10).context("invalid cup")? as _);
+        }
+
+        if cups.is_empty() {
+            bail!("no cups");
+        }
 
-    let output = play_game(cups.clone(), 100);
-    println!("part A: {}", output.iter().join(""));
+        Ok(cups)
+    }
 
-    while cups.len() < 1_000_000 {
-        cups.push(cups.len() + 1);
+    fn part_a(cups: &Self::Input) -> Result<Answer> {
+        let output = play_game(cups.clone(), 100);
+        Ok(output.iter().join("").into())
     }
 
-    let output = play_game(cups.clone(), 10_000_000);
-    println!("part B: {}", output[1] * output[2]);
+    fn part_b(cups: &Self::Input) -> Result<Answer> {
+        let mut cups = cups.clone();
+        while cups.len() < 1_000_000 {
+            cups.push(cups.len() + 1);
+        }
 
-    Ok(())
+        let output = play_game(cups, 10_000_000);
+        Ok((output[1] * output[2]).into())
+    }
 }
 
 #[cfg(test)]
@@ -81,4 +97,10 @@ mod test {
         // let mut output = play_game(input, 10_000_000);
         //assert_eq!(&output[..3], &[1, 934001, 159792]);
     }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Day23::parse("389\n").unwrap(), [3, 8, 9]);
+        assert!(Day23::parse("\n").is_err());
+    }
 }
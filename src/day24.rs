@@ -1,91 +1,73 @@
+use crate::automaton::{Rule, Sparse};
 use crate::common::*;
+use crate::hex::{self, Direction, Hex};
 
-type Tile = (i32, i32);
+/// The floor on the first `days` days, drawn with `#` for black tiles and `.`
+/// for white ones. All drawings cover the same area.
+pub fn floor(black_tiles: &HashSet<Hex>, days: usize) -> Vec<String> {
+    let mut automaton = Sparse::new(black_tiles.iter().copied(), hex::neighborhood());
+    let mut generations = vec![automaton.alive().clone()];
 
-fn decode_tile(line: &str) -> Result<Tile> {
-    let (mut x, mut y) = (0, 0);
-    let mut cmd = "".to_string();
-
-    for c in line.chars() {
-        cmd.push(c);
-
-        match &*cmd {
-            "e" => x += 1,
-            "w" => x -= 1,
-            "se" => y += 1,
-            "nw" => y -= 1,
-            "ne" => {
-                x += 1;
-                y -= 1;
-            }
-            "sw" => {
-                x -= 1;
-                y += 1;
-            }
-            _ => continue,
-        }
-
-        cmd.clear();
+    for _ in 0..days {
+        automaton.step(&flip_rule());
+        generations.push(automaton.alive().clone());
     }
 
-    if !cmd.is_empty() {
-        bail!("invalid command {:?}", cmd);
-    }
+    let area = generations.iter().flatten().copied().collect_vec();
+    generations
+        .iter()
+        .map(|black| {
+            hex::render(area.iter().copied(), |h| {
+                if black.contains(&h) {
+                    '#'
+                } else {
+                    '.'
+                }
+            })
+        })
+        .collect()
+}
 
-    Ok((x, y))
+/// Black tiles with zero or more than two black neighbors flip to white,
+/// white tiles with exactly two black neighbors flip to black.
+fn flip_rule() -> Rule {
+    Rule::new(&[2], &[1, 2])
 }
 
-fn parse_input(lines: &[String]) -> Result<HashSet<Tile>> {
-    let mut tiles = HashSet::<Tile>::default();
+pub struct Day24;
 
-    for line in lines {
-        let coords = decode_tile(line)?;
+impl Solver for Day24 {
+    const DAY: usize = 24;
 
-        if !tiles.insert(coords) {
-            tiles.remove(&coords);
-        }
-    }
+    /// The coordinates of the black tiles.
+    type Input = HashSet<Hex>;
 
-    Ok(tiles)
-}
-
-fn flip_tiles(black_tiles: &mut HashSet<Tile>) {
-    let mut count = HashMap::<Tile, usize>::default();
+    fn parse(input: &str) -> Result<Self::Input> {
+        let mut tiles = HashSet::<Hex>::default();
 
-    for &(x, y) in &*black_tiles {
-        let _ = *count.entry((x, y)).or_default();
+        for line in input.lines() {
+            let coords = Hex::from_path(&Direction::parse_path(line)?);
 
-        for &i in &[
-            (x + 1, y),
-            (x - 1, y),
-            (x, y + 1),
-            (x, y - 1),
-            (x + 1, y - 1),
-            (x - 1, y + 1),
-        ] {
-            *count.entry(i).or_default() += 1;
+            if !tiles.insert(coords) {
+                tiles.remove(&coords);
+            }
         }
+
+        Ok(tiles)
     }
 
-    for (i, n) in count {
-        if n == 2 {
-            black_tiles.insert(i);
-        } else if n != 1 {
-            black_tiles.remove(&i);
-        }
+    fn part_a(black_tiles: &Self::Input) -> Result<Answer> {
+        Ok(black_tiles.len().into())
     }
-}
 
-pub fn run() -> Result {
-    let mut black_tiles = parse_input(&read_input("day24")?)?;
+    fn part_b(black_tiles: &Self::Input) -> Result<Answer> {
+        let rule = flip_rule();
+        let mut automaton = Sparse::new(black_tiles.iter().copied(), hex::neighborhood());
 
-    println!("part A: {:?}", black_tiles.len());
+        for _ in 0..100 {
+            automaton.step(&rule);
+        }
 
-    for _ in 0..100 {
-        flip_tiles(&mut black_tiles);
+        Ok(automaton.population().into())
     }
-
-    println!("part B: {:?}", black_tiles.len());
-
-    Ok(())
 }
@@ -15,19 +15,37 @@ fn encrypt(sub: usize, it: usize) -> usize {
     (0..it).fold(1, |acc, _| (acc * sub) % 20201227)
 }
 
-pub fn run() -> Result {
-    let numbers = read_input("day25")?
-        .into_iter()
-        .map(|s| s.parse::<usize>())
-        .collect::<Result<Vec<_>, _>>()?;
+pub struct Day25;
 
-    let card = numbers[0];
-    let door = numbers[1];
-    let answer = encrypt(door, decrypt(7, card));
+impl Solver for Day25 {
+    const DAY: usize = 25;
 
-    println!("answer A: {}", answer);
+    // Day 25 only has a single puzzle, there is no part B.
+    const HAS_PART_B: bool = false;
 
-    Ok(())
+    /// The public keys of the card and the door.
+    type Input = (usize, usize);
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let numbers = input
+            .lines()
+            .map(|s| s.parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match numbers[..] {
+            [card, door] => Ok((card, door)),
+            _ => bail!("expecting two public keys"),
+        }
+    }
+
+    fn part_a(&(card, door): &Self::Input) -> Result<Answer> {
+        let answer = encrypt(door, decrypt(7, card));
+        Ok(answer.into())
+    }
+
+    fn part_b(_: &Self::Input) -> Result<Answer> {
+        bail!("day 25 has no part B")
+    }
 }
 
 #[cfg(test)]
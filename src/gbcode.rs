@@ -1,56 +1,333 @@
+//! A small virtual machine for the handheld boot code of day 8.
+//!
+//! Programs have one instruction per line. Besides the original `nop`, `acc`
+//! and `jmp`, the VM has registers (`acc` and `r1` to `r7`), arithmetic
+//! (`set`, `add`, `sub`, `mul`), conditional jumps (`jz`, `jnz`, `jlz`), a
+//! memory of integer cells (`ld`, `st`) and I/O (`in`, `out`). Operands are a
+//! register or a signed number, and jump offsets are relative to the jump:
+//!
+//! ```text
+//! in r1
+//! set r2 +1
+//! jz r1 +4
+//! mul r2 r1
+//! sub r1 +1
+//! jmp -3
+//! out r2
+//! ```
+//!
+//! The [`asm`] module assembles programs with labels, comments and directives.
+//!
+//! By default, registers are `i32`s and arithmetic that overflows stops the
+//! process with a [`Fault`]. [`Program::run_with`] selects another [`Word`]
+//! type (`i64` or `BigInt`) and [`Overflow`] mode.
 use crate::common::*;
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs;
 use std::ops::{Index, IndexMut};
+use std::str::FromStr;
 
-#[derive(Clone, Copy)]
+pub mod asm;
+pub mod cfg;
+pub mod debugger;
+pub mod repair;
+mod word;
+
+pub use word::{Overflow, Word};
+
+/// Number of registers of a process. Register 0 is the accumulator `acc`,
+/// the others are named `r1` to `r7`.
+pub const NUM_REGISTERS: usize = 8;
+
+/// A register of the VM.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Reg(u8);
+
+impl Reg {
+    pub const ACC: Reg = Reg(0);
+
+    pub fn new(index: usize) -> Option<Self> {
+        if index < NUM_REGISTERS {
+            Some(Reg(index as u8))
+        } else {
+            None
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl FromStr for Reg {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s == "acc" {
+            return Ok(Reg::ACC);
+        }
+
+        s.strip_prefix('r')
+            .and_then(|i| i.parse().ok())
+            .filter(|&i| i > 0)
+            .and_then(Reg::new)
+            .ok_or_else(|| anyhow!("unknown register {:?}", s))
+    }
+}
+
+impl fmt::Display for Reg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            0 => write!(f, "acc"),
+            i => write!(f, "r{}", i),
+        }
+    }
+}
+
+/// A source operand: either a register or an immediate value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operand {
+    Reg(Reg),
+    Imm(i32),
+}
+
+impl FromStr for Operand {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s.starts_with(|c: char| c == '+' || c == '-' || c.is_ascii_digit()) {
+            let v = s.parse().map_err(|_| anyhow!("invalid number {:?}", s))?;
+            Ok(Operand::Imm(v))
+        } else {
+            Ok(Operand::Reg(s.parse()?))
+        }
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operand::Reg(r) => write!(f, "{}", r),
+            Operand::Imm(v) => write!(f, "{:+}", v),
+        }
+    }
+}
+
+/// An instruction. Jump offsets are relative to the jump itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Instr {
+    /// Does nothing, the argument is ignored.
     Nop(i32),
+    /// Adds the argument to `acc`.
     Acc(i32),
+    /// Jumps unconditionally.
     Jmp(i32),
+    /// `set r x`: stores `x` in `r`.
+    Set(Reg, Operand),
+    /// `add r x`: adds `x` to `r`.
+    Add(Reg, Operand),
+    /// `sub r x`: subtracts `x` from `r`.
+    Sub(Reg, Operand),
+    /// `mul r x`: multiplies `r` by `x`.
+    Mul(Reg, Operand),
+    /// `jz x off`: jumps if `x` is zero.
+    Jz(Operand, i32),
+    /// `jnz x off`: jumps if `x` is not zero.
+    Jnz(Operand, i32),
+    /// `jlz x off`: jumps if `x` is less than zero.
+    Jlz(Operand, i32),
+    /// `ld r addr`: loads the memory cell at `addr` into `r`.
+    Ld(Reg, Operand),
+    /// `st x addr`: stores `x` in the memory cell at `addr`.
+    St(Operand, Operand),
+    /// `in r`: reads the next input value into `r`.
+    In(Reg),
+    /// `out x`: writes `x` to the output.
+    Out(Operand),
 }
 
 impl Instr {
     pub fn parse(s: &str) -> Result<Self> {
-        let mut parts = s.trim().split_whitespace();
+        Self::parse_line(s).with_context(|| format!("while parsing line {:?}", s))
+    }
+
+    fn parse_line(s: &str) -> Result<Self> {
+        let mut parts = s.split_whitespace();
         let instr = parts.next().unwrap_or_default();
-        let arg = parts
-            .next()
-            .unwrap_or_default()
-            .parse()
-            .with_context(|| format!("while parsing line {:?}", s))?;
+        let args: Vec<_> = parts.collect();
+
+        Self::from_parts(instr, &args).map_err(|(_, e)| e)
+    }
+
+    /// Parses an instruction from its mnemonic and arguments. On failure, the
+    /// error comes with the index of the offending argument, if there is one.
+    pub(crate) fn from_parts<S: AsRef<str>>(
+        instr: &str,
+        args: &[S],
+    ) -> Result<Self, (Option<usize>, Error)> {
+        use Instr::*;
+
+        let arity = match instr {
+            "nop" | "acc" | "jmp" | "in" | "out" => 1,
+            "set" | "add" | "sub" | "mul" | "jz" | "jnz" | "jlz" | "ld" | "st" => 2,
+            _ => return Err((None, anyhow!("unknown instruction {:?}", instr))),
+        };
+
+        if args.len() != arity {
+            let msg = format!(
+                "instruction {:?} expects {} argument(s), found {}",
+                instr,
+                arity,
+                args.len()
+            );
+            return Err((None, Error::msg(msg)));
+        }
+
+        let arg = |i: usize| args[i].as_ref();
+        let num = |i: usize| -> Result<i32, _> {
+            arg(i)
+                .parse()
+                .map_err(|_| (Some(i), anyhow!("invalid number {:?}", arg(i))))
+        };
+        let reg = |i: usize| -> Result<Reg, _> { arg(i).parse().map_err(|e| (Some(i), e)) };
+        let op = |i: usize| -> Result<Operand, _> { arg(i).parse().map_err(|e| (Some(i), e)) };
 
         Ok(match instr {
-            "nop" => Instr::Nop(arg),
-            "acc" => Instr::Acc(arg),
-            "jmp" => Instr::Jmp(arg),
-            _ => bail!("unknown instruction {:?}", instr),
+            "nop" => Nop(num(0)?),
+            "acc" => Acc(num(0)?),
+            "jmp" => Jmp(num(0)?),
+            "set" => Set(reg(0)?, op(1)?),
+            "add" => Add(reg(0)?, op(1)?),
+            "sub" => Sub(reg(0)?, op(1)?),
+            "mul" => Mul(reg(0)?, op(1)?),
+            "jz" => Jz(op(0)?, num(1)?),
+            "jnz" => Jnz(op(0)?, num(1)?),
+            "jlz" => Jlz(op(0)?, num(1)?),
+            "ld" => Ld(reg(0)?, op(1)?),
+            "st" => St(op(0)?, op(1)?),
+            "in" => In(reg(0)?),
+            "out" => Out(op(0)?),
+            _ => unreachable!(),
         })
     }
+
+    /// The index of the argument that is the jump offset, for the mnemonic of
+    /// a (conditional) jump.
+    pub(crate) fn jump_arg(instr: &str) -> Option<usize> {
+        match instr {
+            "jmp" => Some(0),
+            "jz" | "jnz" | "jlz" => Some(1),
+            _ => None,
+        }
+    }
+
+    /// The relative jump offset of a (conditional) jump.
+    pub fn jump_offset(&self) -> Option<i32> {
+        match *self {
+            Instr::Jmp(off) | Instr::Jz(_, off) | Instr::Jnz(_, off) | Instr::Jlz(_, off) => {
+                Some(off)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Instr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Instr::*;
+
+        match self {
+            Nop(v) => write!(f, "nop {:+}", v),
+            Acc(v) => write!(f, "acc {:+}", v),
+            Jmp(v) => write!(f, "jmp {:+}", v),
+            Set(r, x) => write!(f, "set {} {}", r, x),
+            Add(r, x) => write!(f, "add {} {}", r, x),
+            Sub(r, x) => write!(f, "sub {} {}", r, x),
+            Mul(r, x) => write!(f, "mul {} {}", r, x),
+            Jz(x, off) => write!(f, "jz {} {:+}", x, off),
+            Jnz(x, off) => write!(f, "jnz {} {:+}", x, off),
+            Jlz(x, off) => write!(f, "jlz {} {:+}", x, off),
+            Ld(r, addr) => write!(f, "ld {} {}", r, addr),
+            St(x, addr) => write!(f, "st {} {}", x, addr),
+            In(r) => write!(f, "in {}", r),
+            Out(x) => write!(f, "out {}", x),
+        }
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Program(Vec<Instr>);
 
 impl Program {
-    pub fn parse_input(filename: &str) -> Result<Self> {
-        let instrs = read_input(filename)?
-            .iter()
-            .map(|s| Instr::parse(&s))
+    pub fn new(instrs: Vec<Instr>) -> Self {
+        Self(instrs)
+    }
+
+    /// Parses a program with one instruction per line. See [`asm::assemble`]
+    /// for labels, comments and directives.
+    pub fn parse(source: &str) -> Result<Self> {
+        let instrs = source
+            .lines()
+            .enumerate()
+            .map(|(i, line)| Instr::parse(line).with_context(|| format!("on line {}", i + 1)))
             .collect::<Result<Vec<_>>>()?;
 
         Ok(Self(instrs.into()))
     }
 
+    /// Assembles the program in the given file, or the input of day 8.
+    pub fn load(path: Option<&str>) -> Result<Self> {
+        let source = match path {
+            Some(path) => {
+                fs::read_to_string(path).with_context(|| format!("failed to read {}", path))?
+            }
+            None => read_input_string("day08")?,
+        };
+
+        Ok(asm::assemble(&source)?)
+    }
+
+    pub fn parse_input(filename: &str) -> Result<Self> {
+        Self::parse(&read_input(filename)?.join("\n"))
+    }
+
+    /// Starts a process with `i32` registers that traps on overflow.
     pub fn run(&self) -> Process {
+        self.run_with(Overflow::Trap)
+    }
+
+    /// Starts a process with registers of type `W` and the given overflow mode.
+    pub fn run_with<W: Word>(&self, overflow: Overflow) -> Process<W> {
         Process {
             program: self.clone(),
+            overflow,
             pc: 0,
-            acc: 0,
+            steps: 0,
+            regs: Default::default(),
+            memory: HashMap::default(),
+            input: VecDeque::new(),
+            output: vec![],
+            trace: None,
         }
     }
 
+    /// Starts a process that records every instruction it executes.
+    pub fn run_traced(&self) -> Process {
+        self.run().with_trace()
+    }
+
     pub fn len(&self) -> usize {
         self.0.len()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn instrs(&self) -> &[Instr] {
+        &self.0
+    }
 }
 
 impl Index<usize> for Program {
@@ -67,45 +344,434 @@ impl IndexMut<usize> for Program {
     }
 }
 
-pub struct Process {
+/// One executed instruction: its pc and the change it made to `acc`. For
+/// fixed-width words, the change wraps around.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Step<W = i32> {
+    pub pc: i32,
+    pub acc_delta: W,
+}
+
+/// How a program run by [`Process::run_to_halt_or_loop`] ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome<W = i32> {
+    /// The program ran off its end with this accumulator.
+    Terminated(W),
+    /// The instruction at `first_repeat_pc` was about to execute for the
+    /// second time, with `acc` the accumulator just before it.
+    Looped { first_repeat_pc: i32, acc: W },
+    /// The `in` instruction at this pc found no input to read.
+    AwaitingInput(i32),
+    /// An instruction failed, or the program jumped outside of itself, see
+    /// [`Fault`].
+    Faulted(Fault),
+    /// The process executed its maximum number of steps, see
+    /// [`Process::run_with_limit`]. The instruction at `pc` is next.
+    BudgetExhausted { pc: i32, acc: W },
+}
+
+/// Why a process could not execute an instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// The pc is outside of the program.
+    OutOfBounds(i32),
+    /// An arithmetic instruction overflowed in [`Overflow::Trap`] mode, or a
+    /// jump overflowed the pc.
+    Overflow { pc: i32, instr: Instr },
+    /// The `in` instruction at this pc found no input to read.
+    NoInput(i32),
+    /// The instruction at this pc used a memory address that does not fit in
+    /// an `i64`.
+    BadAddress(i32),
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Fault::OutOfBounds(pc) => write!(f, "program counter {} out of bounds", pc),
+            Fault::Overflow { pc, instr } => {
+                write!(f, "overflow in {:?} at pc {}", instr.to_string(), pc)
+            }
+            Fault::NoInput(pc) => write!(f, "no input available for `in` at pc {}", pc),
+            Fault::BadAddress(pc) => write!(f, "invalid memory address at pc {}", pc),
+        }
+    }
+}
+
+impl std::error::Error for Fault {}
+
+#[derive(Clone, Debug)]
+pub struct Process<W = i32> {
     program: Program,
+    overflow: Overflow,
     pc: i32,
-    acc: i32,
+    steps: u64,
+    regs: [W; NUM_REGISTERS],
+    memory: HashMap<i64, W>,
+    input: VecDeque<W>,
+    output: Vec<W>,
+    trace: Option<Vec<Step<W>>>,
 }
 
-impl Process {
-    pub fn advance(&mut self) -> Result {
+impl<W: Word> Process<W> {
+    /// Records every instruction this process executes from now on.
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(vec![]);
+        self
+    }
+
+    pub fn advance(&mut self) -> Result<(), Fault> {
         use Instr::*;
 
-        if self.pc < 0 || self.pc as usize >= self.program.len() {
-            bail!("program counter out of bounds");
-        }
+        let pc = self.pc;
+        let instr = self.current().ok_or(Fault::OutOfBounds(pc))?;
+        let overflow = Fault::Overflow { pc, instr };
+        let jump = |off: i32| pc.checked_add(off).ok_or(overflow);
+        let acc = self.acc();
+        let mut next = jump(1)?;
 
-        match self.program[self.pc as usize] {
-            Nop(_) => {
-                self.pc += 1;
+        match instr {
+            Nop(_) => {}
+            Acc(v) => self.apply(Reg::ACC, W::from(v), W::add_with, overflow)?,
+            Jmp(v) => next = jump(v)?,
+            Set(r, x) => self.regs[r.index()] = self.value(x),
+            Add(r, x) => self.apply(r, self.value(x), W::add_with, overflow)?,
+            Sub(r, x) => self.apply(r, self.value(x), W::sub_with, overflow)?,
+            Mul(r, x) => self.apply(r, self.value(x), W::mul_with, overflow)?,
+            Jz(x, v) => {
+                if self.value(x) == W::default() {
+                    next = jump(v)?;
+                }
             }
-            Acc(v) => {
-                self.acc += v;
-                self.pc += 1;
+            Jnz(x, v) => {
+                if self.value(x) != W::default() {
+                    next = jump(v)?;
+                }
             }
-            Jmp(v) => {
-                self.pc += v;
+            Jlz(x, v) => {
+                if self.value(x) < W::default() {
+                    next = jump(v)?;
+                }
             }
+            Ld(r, addr) => {
+                let addr = self.address(addr)?;
+                self.regs[r.index()] = self.load(addr);
+            }
+            St(x, addr) => {
+                let addr = self.address(addr)?;
+                self.memory.insert(addr, self.value(x));
+            }
+            In(r) => {
+                let v = self.input.pop_front().ok_or(Fault::NoInput(pc))?;
+                self.regs[r.index()] = v;
+            }
+            Out(x) => {
+                let v = self.value(x);
+                self.output.push(v);
+            }
+        }
+
+        self.pc = next;
+        self.steps += 1;
+
+        if let Some(trace) = &mut self.trace {
+            let acc_delta = self.regs[0].sub_with(&acc, Overflow::Wrap).unwrap();
+            trace.push(Step { pc, acc_delta });
+        }
+
+        Ok(())
+    }
+
+    fn value(&self, x: Operand) -> W {
+        match x {
+            Operand::Reg(r) => self.regs[r.index()].clone(),
+            Operand::Imm(v) => W::from(v),
         }
+    }
+
+    fn address(&self, x: Operand) -> Result<i64, Fault> {
+        self.value(x).to_address().ok_or(Fault::BadAddress(self.pc))
+    }
 
+    /// Replaces `r` by `op(r, x)`, using the overflow mode of this process.
+    fn apply(
+        &mut self,
+        r: Reg,
+        x: W,
+        op: fn(&W, &W, Overflow) -> Option<W>,
+        fault: Fault,
+    ) -> Result<(), Fault> {
+        let reg = &mut self.regs[r.index()];
+        *reg = op(reg, &x, self.overflow).ok_or(fault)?;
         Ok(())
     }
 
+    /// Runs until the program terminates, an instruction is about to execute
+    /// for the second time, the pc leaves the program, or it needs input that
+    /// is not available.
+    ///
+    /// Loops are detected on the pc alone, which is exact for programs that
+    /// only use `nop`, `acc` and `jmp`. With conditional jumps, running an
+    /// instruction twice does not imply that the program never terminates.
+    pub fn run_to_halt_or_loop(&mut self) -> Outcome<W> {
+        let mut visited = vec![false; self.program.len()];
+
+        loop {
+            if self.done() {
+                return Outcome::Terminated(self.acc());
+            }
+
+            if self.current().is_none() {
+                return Outcome::Faulted(Fault::OutOfBounds(self.pc));
+            }
+
+            if let Some(Instr::In(_)) = self.current() {
+                if self.input.is_empty() {
+                    return Outcome::AwaitingInput(self.pc);
+                }
+            }
+
+            let seen = &mut visited[self.pc as usize];
+            if *seen {
+                return Outcome::Looped {
+                    first_repeat_pc: self.pc,
+                    acc: self.acc(),
+                };
+            }
+
+            *seen = true;
+            if let Err(fault) = self.advance() {
+                return Outcome::Faulted(fault);
+            }
+        }
+    }
+
+    /// Runs until the program terminates, the pc leaves the program, it needs
+    /// input that is not available, or it executed `max_steps` instructions in
+    /// total. Unlike [`run_to_halt_or_loop`](Self::run_to_halt_or_loop), this
+    /// does not look for loops, so it is suitable for any program.
+    pub fn run_with_limit(&mut self, max_steps: u64) -> Outcome<W> {
+        loop {
+            match self.current() {
+                None if self.done() => return Outcome::Terminated(self.acc()),
+                None => return Outcome::Faulted(Fault::OutOfBounds(self.pc)),
+                Some(Instr::In(_)) if self.input.is_empty() => {
+                    return Outcome::AwaitingInput(self.pc)
+                }
+                Some(_) if self.steps >= max_steps => {
+                    return Outcome::BudgetExhausted {
+                        pc: self.pc,
+                        acc: self.acc(),
+                    }
+                }
+                Some(_) => {}
+            }
+
+            if let Err(fault) = self.advance() {
+                return Outcome::Faulted(fault);
+            }
+        }
+    }
+
     pub fn done(&self) -> bool {
         self.pc == self.program.len() as i32
     }
 
+    /// The instruction under the program counter, if it is in bounds.
+    pub fn current(&self) -> Option<Instr> {
+        if self.pc < 0 {
+            return None;
+        }
+
+        self.program.instrs().get(self.pc as usize).copied()
+    }
+
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
     pub fn pc(&self) -> i32 {
         self.pc
     }
 
-    pub fn acc(&self) -> i32 {
-        self.acc
+    /// The number of instructions executed so far.
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    pub fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    pub fn acc(&self) -> W {
+        self.regs[0].clone()
+    }
+
+    pub fn reg(&self, r: Reg) -> W {
+        self.regs[r.index()].clone()
+    }
+
+    pub fn regs(&self) -> &[W] {
+        &self.regs
+    }
+
+    /// The value of a memory cell, all cells start at zero.
+    pub fn load(&self, addr: i64) -> W {
+        self.memory.get(&addr).cloned().unwrap_or_default()
+    }
+
+    /// Adds a value to the end of the input queue.
+    pub fn push_input(&mut self, v: W) {
+        self.input.push_back(v);
+    }
+
+    /// The values written by `out` so far.
+    pub fn output(&self) -> &[W] {
+        &self.output
+    }
+
+    /// The executed instructions in order, or `None` if this process is not
+    /// traced (see [`Process::with_trace`]).
+    pub fn trace(&self) -> Option<&[Step<W>]> {
+        self.trace.as_deref()
+    }
+}
+
+/// The example program of day 8, which loops forever.
+#[cfg(test)]
+pub(crate) const EXAMPLE: &str =
+    "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use num_bigint::BigInt;
+
+    #[test]
+    fn test_outcome() {
+        let mut program = Program::parse(EXAMPLE).unwrap();
+        let mut p = program.run_traced();
+        let outcome = p.run_to_halt_or_loop();
+
+        assert_eq!(
+            outcome,
+            Outcome::Looped {
+                first_repeat_pc: 1,
+                acc: 5
+            }
+        );
+
+        let pcs: Vec<_> = p.trace().unwrap().iter().map(|s| s.pc).collect();
+        assert_eq!(pcs, [0, 1, 2, 6, 7, 3, 4]);
+        assert_eq!(
+            p.trace().unwrap()[5],
+            Step {
+                pc: 3,
+                acc_delta: 3
+            }
+        );
+
+        program[7] = Instr::Nop(-4);
+        assert_eq!(program.run().run_to_halt_or_loop(), Outcome::Terminated(8));
+
+        program[0] = Instr::Jmp(-1);
+        assert_eq!(
+            program.run().run_to_halt_or_loop(),
+            Outcome::Faulted(Fault::OutOfBounds(-1))
+        );
+    }
+
+    #[test]
+    fn test_extended() {
+        // Reads n and writes n! to memory cell 10 and to the output.
+        let source = "in r1\nset r2 +1\njz r1 +4\nmul r2 r1\nsub r1 +1\njmp -3\nst r2 +10\nld acc +10\nout acc";
+        let program = Program::parse(source).unwrap();
+        assert_eq!(program[3], Instr::Mul(Reg(2), Operand::Reg(Reg(1))));
+
+        for (line, instr) in source.lines().zip(program.instrs()) {
+            assert_eq!(line, instr.to_string());
+        }
+
+        let mut p = program.run();
+        assert_eq!(p.run_to_halt_or_loop(), Outcome::AwaitingInput(0));
+
+        p.push_input(5);
+        while !p.done() {
+            p.advance().unwrap();
+        }
+
+        assert_eq!(p.output(), [120]);
+        assert_eq!((p.acc(), p.load(10), p.reg(Reg(1))), (120, 120, 0));
+
+        assert!(Instr::parse("add r8 +1").is_err());
+        assert!(Instr::parse("set acc").is_err());
+        assert!(Instr::parse("foo +1").is_err());
+    }
+
+    #[test]
+    fn test_overflow() {
+        let program = Program::parse("acc +2147483647\nacc +1").unwrap();
+        let run = |mode| program.run_with::<i32>(mode).run_to_halt_or_loop();
+
+        assert_eq!(
+            program.run().run_to_halt_or_loop(),
+            Outcome::Faulted(Fault::Overflow {
+                pc: 1,
+                instr: Instr::Acc(1)
+            })
+        );
+        assert_eq!(run(Overflow::Wrap), Outcome::Terminated(i32::MIN));
+        assert_eq!(run(Overflow::Saturate), Outcome::Terminated(i32::MAX));
+
+        let mut p = program.run_with::<i64>(Overflow::Trap);
+        assert_eq!(p.run_to_halt_or_loop(), Outcome::Terminated(1 << 31));
+
+        // 2^31 cubed overflows an i64, but not a big integer.
+        let program =
+            Program::parse("set r1 +2147483647\nadd r1 +1\nset acc r1\nmul acc r1\nmul acc r1")
+                .unwrap();
+        let mut p = program.run_with::<i64>(Overflow::Trap);
+        assert!(matches!(
+            p.run_to_halt_or_loop(),
+            Outcome::Faulted(Fault::Overflow { pc: 4, .. })
+        ));
+
+        let mut p = program.run_with::<BigInt>(Overflow::Trap);
+        assert_eq!(
+            p.run_to_halt_or_loop(),
+            Outcome::Terminated(BigInt::from(1) << 93)
+        );
+
+        let program = Program::parse("nop +0\njmp +2147483647").unwrap();
+        assert!(matches!(program.run().advance(), Ok(())));
+        let mut p = program.run();
+        p.advance().unwrap();
+        assert_eq!(
+            p.advance().unwrap_err().to_string(),
+            "overflow in \"jmp +2147483647\" at pc 1"
+        );
+    }
+
+    #[test]
+    fn test_limit() {
+        // Counts down from 1000, then terminates.
+        let program = Program::parse("set r1 +1000\nsub r1 +1\njnz r1 -1").unwrap();
+
+        let mut p = program.run();
+        assert_eq!(
+            p.run_with_limit(100),
+            Outcome::BudgetExhausted { pc: 2, acc: 0 }
+        );
+        assert_eq!((p.steps(), p.reg(Reg(1))), (100, 950));
+
+        // The budget counts all steps, so running again continues.
+        assert_eq!(p.run_with_limit(2001), Outcome::Terminated(0));
+        assert_eq!(p.steps(), 2001);
+
+        let mut p = program.run();
+        assert_eq!(
+            p.run_with_limit(2000),
+            Outcome::BudgetExhausted { pc: 2, acc: 0 }
+        );
     }
 }
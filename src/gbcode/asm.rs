@@ -0,0 +1,299 @@
+//! Assembler for handheld programs.
+//!
+//! The assembler accepts everything [`Program::parse`] does, and in addition:
+//!
+//! - `# comments` until the end of a line, and blank lines;
+//! - labels like `loop:`, either on their own line or in front of an
+//!   instruction. A label can be used as the target of `jmp`, `jz`, `jnz` and
+//!   `jlz`, and is resolved to a relative offset;
+//! - `.equ NAME VALUE` defines a constant that can be used in place of any
+//!   number;
+//! - `.space N` inserts `N` times `nop +0`, up to [`MAX_SPACE`] in total.
+//!
+//! Arguments can be separated by whitespace or commas.
+//!
+//! ```text
+//! .equ n 10
+//!         set r1 n       # counter
+//! loop:   acc +2
+//!         sub r1 +1
+//!         jnz r1 loop
+//! ```
+use super::{Instr, Program, Reg};
+use crate::common::*;
+use std::fmt;
+
+/// The largest number of instructions that `.space` may insert into a program.
+pub const MAX_SPACE: usize = 1 << 20;
+
+/// An error in the source of a program, with the 1-based line and column of
+/// the offending token.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AsmError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for AsmError {}
+
+#[derive(Clone, Copy, Debug)]
+struct Token<'a> {
+    text: &'a str,
+    line: usize,
+    column: usize,
+}
+
+impl Token<'_> {
+    fn error(&self, message: impl fmt::Display) -> AsmError {
+        AsmError {
+            line: self.line,
+            column: self.column,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Splits a line into tokens, dropping comments.
+fn tokenize(line: &str, lineno: usize) -> Vec<Token<'_>> {
+    let line = line.split('#').next().unwrap_or_default();
+    let mut tokens = vec![];
+    let mut start = None;
+
+    for (i, c) in line.char_indices().chain(Some((line.len(), ' '))) {
+        let separator = c.is_whitespace() || c == ',';
+
+        match start {
+            Some(begin) if separator => {
+                tokens.push(Token {
+                    text: &line[begin..i],
+                    line: lineno,
+                    column: line[..begin].chars().count() + 1,
+                });
+                start = None;
+            }
+            None if !separator => start = Some(i),
+            _ => {}
+        }
+    }
+
+    tokens
+}
+
+fn check_name(token: &Token) -> Result<(), AsmError> {
+    let name = token.text;
+    let valid = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    if !valid {
+        Err(token.error(format!("invalid name {:?}", name)))
+    } else if name.parse::<Reg>().is_ok() {
+        Err(token.error(format!(
+            "{:?} is a register and cannot be used as a name",
+            name
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Assembles a program, see the [module documentation](self) for the syntax.
+pub fn assemble(source: &str) -> Result<Program, AsmError> {
+    let mut labels = HashMap::default();
+    let mut constants = HashMap::<&str, i32>::default();
+    let mut lines = vec![];
+    let mut len = 0;
+    let mut space = 0;
+
+    // First pass: find labels and constants, and the position of every instruction.
+    for (i, line) in enumerate(source.lines()) {
+        let mut tokens = &tokenize(line, i + 1)[..];
+
+        while let Some(label) = tokens.first().filter(|t| t.text.ends_with(':')) {
+            let name = Token {
+                text: &label.text[..label.text.len() - 1],
+                ..*label
+            };
+
+            check_name(&name)?;
+            if labels.insert(name.text, len as i32).is_some() {
+                return Err(name.error(format!("label {:?} is defined twice", name.text)));
+            }
+
+            tokens = &tokens[1..];
+        }
+
+        let (head, args) = match tokens.split_first() {
+            Some(split) => split,
+            None => continue,
+        };
+
+        let number = |token: &Token| -> Result<i32, AsmError> {
+            constants
+                .get(token.text)
+                .copied()
+                .or_else(|| token.text.parse().ok())
+                .ok_or_else(|| token.error(format!("invalid number {:?}", token.text)))
+        };
+
+        match head.text {
+            ".equ" => {
+                if let [name, value] = args {
+                    check_name(name)?;
+                    let value = number(value)?;
+
+                    if constants.insert(name.text, value).is_some() {
+                        return Err(name.error(format!("{:?} is defined twice", name.text)));
+                    }
+                } else {
+                    return Err(head.error("usage: .equ NAME VALUE"));
+                }
+            }
+            ".space" => match args {
+                [n] => {
+                    let n = number(n)?;
+                    if n < 0 {
+                        return Err(args[0].error("size cannot be negative"));
+                    }
+
+                    space += n as usize;
+                    if space > MAX_SPACE {
+                        return Err(args[0].error(format!(
+                            "cannot insert more than {} instructions with .space",
+                            MAX_SPACE
+                        )));
+                    }
+
+                    lines.extend((0..n).map(|_| None));
+                    len += n as usize;
+                }
+                _ => return Err(head.error("usage: .space N")),
+            },
+            directive if directive.starts_with('.') => {
+                return Err(head.error(format!("unknown directive {:?}", directive)));
+            }
+            _ => {
+                lines.push(Some((*head, args.to_vec())));
+                len += 1;
+            }
+        }
+    }
+
+    // Second pass: resolve labels and constants, and parse the instructions.
+    let mut instrs = vec![];
+    for (pc, line) in enumerate(lines) {
+        let (head, args) = match line {
+            Some(line) => line,
+            None => {
+                instrs.push(Instr::Nop(0));
+                continue;
+            }
+        };
+
+        let jump_arg = Instr::jump_arg(head.text);
+        let resolved: Vec<String> = enumerate(&args)
+            .map(|(i, arg)| match labels.get(arg.text) {
+                Some(&target) if jump_arg == Some(i) => (target - pc as i32).to_string(),
+                _ => match constants.get(arg.text) {
+                    Some(value) => value.to_string(),
+                    None => arg.text.to_string(),
+                },
+            })
+            .collect();
+
+        let instr = Instr::from_parts(head.text, &resolved).map_err(|(i, e)| {
+            let token = i.map_or(head, |i| args[i]);
+
+            if jump_arg.is_some() && jump_arg == i && check_name(&token).is_ok() {
+                token.error(format!("unknown label {:?}", token.text))
+            } else {
+                token.error(e)
+            }
+        })?;
+
+        instrs.push(instr);
+    }
+
+    Ok(Program::new(instrs))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gbcode::{Operand, Outcome};
+
+    #[test]
+    fn test_assemble() {
+        let source = "
+            # Adds 2 to acc, n times.
+            .equ n 10
+            start:
+                    set r1, n   # counter
+            loop:   acc +2
+                    sub r1 +1
+                    jnz r1 loop
+                    jmp end
+                    .space 2
+            end:    nop +0
+        ";
+
+        let program = assemble(source).unwrap();
+        assert_eq!(program.len(), 8);
+        assert_eq!(
+            program[0],
+            Instr::Set(Reg::new(1).unwrap(), Operand::Imm(10))
+        );
+        assert_eq!(
+            program[3],
+            Instr::Jnz(Operand::Reg(Reg::new(1).unwrap()), -2)
+        );
+        assert_eq!(program[4], Instr::Jmp(3));
+        assert_eq!(program[5], Instr::Nop(0));
+        assert_eq!(
+            program.run().run_to_halt_or_loop(),
+            Outcome::Looped {
+                first_repeat_pc: 1,
+                acc: 2,
+            }
+        );
+
+        // Plain programs assemble to the same thing.
+        let plain = "nop +0\nacc +1\njmp -2";
+        assert_eq!(assemble(plain).unwrap(), Program::parse(plain).unwrap());
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |source| assemble(source).unwrap_err().to_string();
+
+        assert_eq!(
+            error("nop +0\n  jmp nowhere"),
+            "2:7: unknown label \"nowhere\""
+        );
+        assert_eq!(error("add r9, 1"), "1:5: unknown register \"r9\"");
+        assert_eq!(error("\n\n  foo +1"), "3:3: unknown instruction \"foo\"");
+        assert_eq!(error("a:\na: nop +0"), "2:1: label \"a\" is defined twice");
+        assert_eq!(error(".equ x"), "1:1: usage: .equ NAME VALUE");
+        assert_eq!(
+            error(".space 2000000000"),
+            "1:8: cannot insert more than 1048576 instructions with .space"
+        );
+        assert_eq!(
+            error(
+                ".space 1000000
+.space 100000"
+            ),
+            "2:8: cannot insert more than 1048576 instructions with .space"
+        );
+        assert_eq!(
+            error("r1: nop +0"),
+            "1:1: \"r1\" is a register and cannot be used as a name"
+        );
+    }
+}
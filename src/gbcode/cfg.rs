@@ -0,0 +1,285 @@
+//! Control-flow analysis of handheld programs: basic blocks, reachability,
+//! an annotated disassembly listing, and Graphviz DOT export.
+use super::{Instr, Program};
+use crate::common::*;
+use petgraph::prelude::*;
+use petgraph::visit::Dfs;
+use std::fmt::{self, Write as _};
+
+/// A node of the control-flow graph.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Node {
+    /// The instructions `start..end`, which always execute in sequence.
+    Block { start: usize, end: usize },
+    /// The end of the program: jumping here terminates it.
+    Exit,
+    /// A jump target outside of the program.
+    OutOfBounds(i64),
+}
+
+/// How control flows from one node to the next.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edge {
+    /// To the next instruction, including a conditional jump not taken.
+    Next,
+    /// A (conditional) jump that is taken.
+    Jump,
+}
+
+/// The absolute successors of the instruction at `pc`.
+pub fn successors(instr: Instr, pc: usize) -> Vec<(i64, Edge)> {
+    let pc = pc as i64;
+
+    match instr {
+        Instr::Jmp(off) => vec![(pc + off as i64, Edge::Jump)],
+        Instr::Jz(_, off) | Instr::Jnz(_, off) | Instr::Jlz(_, off) if off != 1 => {
+            vec![(pc + 1, Edge::Next), (pc + off as i64, Edge::Jump)]
+        }
+        _ => vec![(pc + 1, Edge::Next)],
+    }
+}
+
+pub struct Cfg {
+    graph: DiGraph<Node, Edge>,
+    /// The block of every instruction.
+    blocks: Vec<NodeIndex>,
+    reachable: Vec<bool>,
+}
+
+impl Cfg {
+    pub fn new(program: &Program) -> Self {
+        let instrs = program.instrs();
+        let n = instrs.len();
+
+        // An instruction starts a block if it is the first one, the target of
+        // a jump, or follows a jump.
+        let mut leader = vec![false; n + 1];
+        leader[0] = true;
+        leader[n] = true;
+
+        for (pc, &instr) in enumerate(instrs) {
+            if instr.jump_offset().is_some() {
+                leader[pc + 1] = true;
+
+                for (target, _) in successors(instr, pc) {
+                    if target >= 0 && target < n as i64 {
+                        leader[target as usize] = true;
+                    }
+                }
+            }
+        }
+
+        let mut graph = DiGraph::new();
+        let mut blocks = Vec::with_capacity(n);
+        let mut start = 0;
+
+        for end in (1..=n).filter(|&pc| leader[pc]) {
+            let node = graph.add_node(Node::Block { start, end });
+            blocks.extend((start..end).map(|_| node));
+            start = end;
+        }
+
+        let exit = graph.add_node(Node::Exit);
+        let mut out_of_bounds = HashMap::default();
+
+        for &node in blocks.iter().dedup() {
+            let last = match graph[node] {
+                Node::Block { end, .. } => end - 1,
+                _ => unreachable!(),
+            };
+
+            for (target, edge) in successors(instrs[last], last) {
+                let to = if target == n as i64 {
+                    exit
+                } else if target < 0 || target > n as i64 {
+                    *out_of_bounds
+                        .entry(target)
+                        .or_insert_with(|| graph.add_node(Node::OutOfBounds(target)))
+                } else {
+                    blocks[target as usize]
+                };
+
+                graph.add_edge(node, to, edge);
+            }
+        }
+
+        let mut reachable = vec![false; graph.node_count()];
+        let mut dfs = Dfs::new(&graph, blocks.first().copied().unwrap_or(exit));
+        while let Some(node) = dfs.next(&graph) {
+            reachable[node.index()] = true;
+        }
+
+        Self {
+            graph,
+            blocks,
+            reachable,
+        }
+    }
+
+    pub fn graph(&self) -> &DiGraph<Node, Edge> {
+        &self.graph
+    }
+
+    /// The block containing the instruction at `pc`.
+    pub fn block_of(&self, pc: usize) -> NodeIndex {
+        self.blocks[pc]
+    }
+
+    /// Whether the node can be reached from the start of the program.
+    pub fn is_reachable(&self, node: NodeIndex) -> bool {
+        self.reachable[node.index()]
+    }
+
+    fn describe(&self, node: NodeIndex) -> String {
+        match self.graph[node] {
+            Node::Block { .. } => format!("B{}", node.index()),
+            Node::Exit => "exit".to_string(),
+            Node::OutOfBounds(pc) => format!("out of bounds ({})", pc),
+        }
+    }
+
+    /// The program as text, with one header per basic block, absolute jump
+    /// targets, and unreachable blocks marked.
+    pub fn listing(&self, program: &Program) -> String {
+        let mut out = String::new();
+        self.write_listing(program, &mut out).unwrap();
+        out
+    }
+
+    fn write_listing(&self, program: &Program, out: &mut String) -> fmt::Result {
+        for (pc, &instr) in enumerate(program.instrs()) {
+            let node = self.blocks[pc];
+
+            if let Node::Block { start, end } = self.graph[node] {
+                if start == pc {
+                    let mark = if self.is_reachable(node) {
+                        ""
+                    } else {
+                        ", unreachable"
+                    };
+                    writeln!(
+                        out,
+                        "{}:  ; pc {}..={}{}",
+                        self.describe(node),
+                        start,
+                        end - 1,
+                        mark
+                    )?;
+                }
+            }
+
+            let jump = self
+                .graph
+                .edges(node)
+                .find(|e| *e.weight() == Edge::Jump && instr.jump_offset().is_some());
+
+            if let Some(edge) = jump {
+                let target = match self.graph[edge.target()] {
+                    Node::Block { start, .. } => start as i64,
+                    Node::Exit => program.len() as i64,
+                    Node::OutOfBounds(target) => target,
+                };
+
+                let instr = instr.to_string();
+                let to = self.describe(edge.target());
+                write!(out, "{:>8}  {:<16}; -> {} ({})", pc, instr, target, to)?;
+            } else {
+                write!(out, "{:>8}  {}", pc, instr)?;
+            }
+
+            writeln!(out)?;
+        }
+
+        Ok(())
+    }
+
+    /// The control-flow graph in Graphviz DOT format. Unreachable blocks are
+    /// drawn gray, jumps are drawn as solid edges and fallthrough as dashed.
+    pub fn to_dot(&self, program: &Program) -> String {
+        let mut out = String::new();
+        self.write_dot(program, &mut out).unwrap();
+        out
+    }
+
+    fn write_dot(&self, program: &Program, out: &mut String) -> fmt::Result {
+        writeln!(out, "digraph {{")?;
+        writeln!(out, "    node [shape=box, fontname=monospace];")?;
+
+        for node in self.graph.node_indices() {
+            let mut label = format!("{}\\l", self.describe(node));
+
+            if let Node::Block { start, end } = self.graph[node] {
+                for pc in start..end {
+                    write!(label, "{:>4}  {}\\l", pc, program[pc])?;
+                }
+            }
+
+            let color = if self.is_reachable(node) {
+                "black"
+            } else {
+                "gray"
+            };
+
+            writeln!(
+                out,
+                "    {} [label=\"{}\", color={}, fontcolor={}];",
+                node.index(),
+                label,
+                color,
+                color
+            )?;
+        }
+
+        for edge in self.graph.edge_references() {
+            let style = match edge.weight() {
+                Edge::Next => "dashed",
+                Edge::Jump => "solid",
+            };
+
+            writeln!(
+                out,
+                "    {} -> {} [style={}];",
+                edge.source().index(),
+                edge.target().index(),
+                style
+            )?;
+        }
+
+        writeln!(out, "}}")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gbcode::EXAMPLE;
+
+    #[test]
+    fn test_cfg() {
+        let program = Program::parse(EXAMPLE).unwrap();
+        let cfg = Cfg::new(&program);
+
+        let blocks = map(0..program.len(), |pc| cfg.block_of(pc).index()).collect_vec();
+        assert_eq!(blocks, [0, 1, 1, 2, 2, 3, 4, 4, 5]);
+
+        // `acc -99` and `acc +6` are never executed.
+        let reachable = map(0..program.len(), |pc| cfg.is_reachable(cfg.block_of(pc)));
+        assert_eq!(
+            reachable.collect_vec(),
+            [true, true, true, true, true, false, true, true, false]
+        );
+
+        let listing = cfg.listing(&program);
+        assert!(listing.contains("B3:  ; pc 5..=5, unreachable\n"));
+        assert!(listing.contains("       2  jmp +4          ; -> 6 (B4)\n"));
+
+        let dot = cfg.to_dot(&program);
+        assert!(dot.starts_with("digraph {\n"));
+        assert!(dot.contains("    2 -> 1 [style=solid];\n"));
+        assert!(dot.contains("    3 -> 4 [style=dashed];\n"));
+
+        let program = Program::parse("nop +0\njmp +2147483647").unwrap();
+        let listing = Cfg::new(&program).listing(&program);
+        assert!(listing.contains("; -> 2147483648 (out of bounds (2147483648))\n"));
+    }
+}
@@ -0,0 +1,416 @@
+//! Interactive debugger for handheld programs.
+//!
+//! Start it with `cargo run -- debug [PATH]`, which loads the program at
+//! `PATH` (or the input of day 8) and reads commands from stdin. Type `help` for the
+//! list of commands.
+use super::{Process, Program, Reg};
+use crate::common::*;
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+use std::io::{self, BufRead, Write};
+
+const HELP: &str = "\
+commands:
+  step [N], s [N]     execute the next N instructions (default: 1)
+  continue, c         run until a breakpoint or the end of the program, for at
+                      most 1000000 instructions
+  loop                run until an instruction is about to execute a second time
+  break PC, b PC      set a breakpoint on the instruction at PC
+  delete PC, d PC     remove the breakpoint at PC
+  breakpoints         list all breakpoints
+  print, p            print pc, acc, and the instruction under the pc
+  list [N], l [N]     list N instructions around the pc (default: 5)
+  watch, w            toggle printing acc every time it changes
+  regs                print all registers and the output so far
+  input V...          append values to the input of the program
+  reset, r            restart the program from the beginning
+  help, h             show this help
+  quit, q             exit the debugger";
+
+/// Why the debugger stopped running the program.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Stop {
+    /// The requested number of steps has been executed.
+    Stepped,
+    /// The pc reached a breakpoint.
+    Breakpoint(i32),
+    /// The instruction at this pc is about to execute for the second time.
+    Loop(i32),
+    /// The program ran off its end.
+    Terminated,
+    /// The step budget ran out before the pc reached a breakpoint or the end,
+    /// see [`Debugger::with_budget`].
+    BudgetExhausted(i32),
+    /// The pc left the program without terminating.
+    Fault(i32),
+}
+
+pub struct Debugger {
+    process: Process,
+    breakpoints: BTreeSet<i32>,
+    visited: HashSet<i32>,
+    watch: bool,
+    budget: usize,
+}
+
+impl Debugger {
+    pub fn new(program: &Program) -> Self {
+        Self {
+            process: program.run(),
+            breakpoints: BTreeSet::new(),
+            visited: HashSet::default(),
+            watch: false,
+            budget: 1_000_000,
+        }
+    }
+
+    /// Sets how many instructions a run without a step count executes at
+    /// most, so that `continue` returns even if the program never ends.
+    pub fn with_budget(mut self, budget: usize) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    pub fn process(&self) -> &Process {
+        &self.process
+    }
+
+    pub fn breakpoints(&self) -> &BTreeSet<i32> {
+        &self.breakpoints
+    }
+
+    pub fn reset(&mut self) {
+        self.process = self.process.program().run();
+        self.visited.clear();
+    }
+
+    /// Executes one instruction, printing `acc` to `out` if it is watched and
+    /// it changed.
+    fn step(&mut self, out: &mut dyn Write) -> Result<Option<Stop>> {
+        if self.process.done() {
+            return Ok(Some(Stop::Terminated));
+        }
+
+        let pc = self.process.pc();
+        if self.process.current().is_none() {
+            return Ok(Some(Stop::Fault(pc)));
+        }
+
+        let before = self.process.acc();
+        self.visited.insert(pc);
+        self.process.advance()?;
+
+        if self.watch && self.process.acc() != before {
+            writeln!(
+                out,
+                "acc: {} -> {} (at pc {})",
+                before,
+                self.process.acc(),
+                pc
+            )?;
+        }
+
+        Ok(None)
+    }
+
+    /// Runs the program until it stops. Breakpoints are only checked after
+    /// the first instruction, so running from a breakpoint makes progress.
+    /// Stops after `limit` instructions if given, or else after the budget,
+    /// and with `detect_loops` before an instruction runs for the second time
+    /// since the start of this run.
+    pub fn run(
+        &mut self,
+        limit: Option<usize>,
+        detect_loops: bool,
+        out: &mut dyn Write,
+    ) -> Result<Stop> {
+        let mut count = 0;
+        self.visited.clear();
+
+        loop {
+            if limit == Some(count) {
+                return Ok(Stop::Stepped);
+            } else if limit.is_none() && count == self.budget {
+                return Ok(Stop::BudgetExhausted(self.process.pc()));
+            }
+
+            if let Some(stop) = self.step(out)? {
+                return Ok(stop);
+            }
+
+            count += 1;
+            let pc = self.process.pc();
+
+            if self.process.done() {
+                return Ok(Stop::Terminated);
+            } else if detect_loops && self.visited.contains(&pc) {
+                return Ok(Stop::Loop(pc));
+            } else if limit.is_none() && self.breakpoints.contains(&pc) {
+                return Ok(Stop::Breakpoint(pc));
+            }
+        }
+    }
+
+    fn print_state(&self, out: &mut dyn Write) -> Result {
+        let p = &self.process;
+        let instr = match p.current() {
+            Some(instr) => instr.to_string(),
+            None if p.done() => "<end of program>".to_string(),
+            None => "<out of bounds>".to_string(),
+        };
+
+        writeln!(
+            out,
+            "pc: {}  acc: {}  steps: {}  next: {}",
+            p.pc(),
+            p.acc(),
+            p.steps(),
+            instr
+        )?;
+        Ok(())
+    }
+
+    fn print_stop(&self, stop: &Stop, out: &mut dyn Write) -> Result {
+        match stop {
+            Stop::Stepped => {}
+            Stop::Breakpoint(pc) => writeln!(out, "breakpoint at pc {}", pc)?,
+            Stop::Loop(pc) => writeln!(out, "loop detected: pc {} is about to run again", pc)?,
+            Stop::Terminated => writeln!(out, "program terminated")?,
+            Stop::BudgetExhausted(pc) => writeln!(
+                out,
+                "stopped at pc {} after {} instructions",
+                pc, self.budget
+            )?,
+            Stop::Fault(pc) => writeln!(out, "program counter {} is out of bounds", pc)?,
+        }
+
+        self.print_state(out)
+    }
+
+    fn list(&self, n: usize, out: &mut dyn Write) -> Result {
+        let n = i64::try_from(n).map_err(|_| anyhow!("count {} is too large", n))?;
+        let instrs = self.process.program().instrs();
+        let pc = self.process.pc() as i64;
+        let begin = (pc - n / 2).max(0);
+        let end = begin.saturating_add(n).min(instrs.len() as i64);
+
+        for i in begin..end {
+            let marker = if i == pc { "=>" } else { "  " };
+            let bp = if self.breakpoints.contains(&(i as i32)) {
+                "*"
+            } else {
+                " "
+            };
+            writeln!(out, "{}{}{:>5}  {}", marker, bp, i, instrs[i as usize])?;
+        }
+
+        Ok(())
+    }
+
+    /// Executes a single command, writing its output to `out`. Returns `false`
+    /// if the debugger should exit.
+    pub fn execute(&mut self, line: &str, out: &mut dyn Write) -> Result<bool> {
+        let mut words = line.split_whitespace();
+        let cmd = match words.next() {
+            Some(cmd) => cmd,
+            None => return Ok(true),
+        };
+
+        let arg = words.next();
+        let parse_pc = |arg: Option<&str>| -> Result<i32> {
+            let arg = arg.context("expecting a program counter")?;
+            arg.parse()
+                .map_err(|_| anyhow!("invalid program counter {:?}", arg))
+        };
+        let parse_count = |arg: Option<&str>, default| -> Result<usize> {
+            arg.map_or(Ok(default), |s| {
+                s.parse().map_err(|_| anyhow!("invalid count {:?}", s))
+            })
+        };
+
+        match cmd {
+            "step" | "s" => {
+                let n = parse_count(arg, 1)?;
+                let stop = self.run(Some(n), false, out)?;
+                self.print_stop(&stop, out)?;
+            }
+            "continue" | "c" => {
+                let stop = self.run(None, false, out)?;
+                self.print_stop(&stop, out)?;
+            }
+            "loop" => {
+                let breakpoints = std::mem::take(&mut self.breakpoints);
+                let stop = self.run(None, true, out);
+                self.breakpoints = breakpoints;
+                self.print_stop(&stop?, out)?;
+            }
+            "break" | "b" => {
+                let pc = parse_pc(arg)?;
+                if pc < 0 || pc as usize >= self.process.program().len() {
+                    bail!("no instruction at pc {}", pc);
+                }
+
+                self.breakpoints.insert(pc);
+                writeln!(out, "breakpoint set at pc {}", pc)?;
+            }
+            "delete" | "d" => {
+                let pc = parse_pc(arg)?;
+                if !self.breakpoints.remove(&pc) {
+                    bail!("no breakpoint at pc {}", pc);
+                }
+
+                writeln!(out, "breakpoint at pc {} removed", pc)?;
+            }
+            "breakpoints" => {
+                for pc in &self.breakpoints {
+                    writeln!(out, "{:>5}  {}", pc, self.process.program()[*pc as usize])?;
+                }
+            }
+            "print" | "p" => self.print_state(out)?,
+            "list" | "l" => self.list(parse_count(arg, 5)?, out)?,
+            "regs" => {
+                for (i, v) in self.process.regs().iter().enumerate() {
+                    let reg = Reg::new(i).unwrap();
+                    writeln!(out, "{:>4}: {}", reg.to_string(), v)?;
+                }
+
+                writeln!(out, "output: {:?}", self.process.output())?;
+            }
+            "input" => {
+                for word in arg.into_iter().chain(words) {
+                    let v = word
+                        .parse()
+                        .map_err(|_| anyhow!("invalid number {:?}", word))?;
+                    self.process.push_input(v);
+                }
+            }
+            "watch" | "w" => {
+                self.watch = !self.watch;
+                let state = if self.watch { "on" } else { "off" };
+                writeln!(out, "watching acc: {}", state)?;
+            }
+            "reset" | "r" => {
+                self.reset();
+                self.print_state(out)?;
+            }
+            "help" | "h" => writeln!(out, "{}", HELP)?,
+            "quit" | "q" => return Ok(false),
+            _ => bail!(
+                "unknown command {:?}, type `help` for a list of commands",
+                cmd
+            ),
+        }
+
+        Ok(true)
+    }
+}
+
+/// Runs the debugger REPL on the given program, reading commands from stdin.
+pub fn main(program: &Program) -> Result {
+    let mut debugger = Debugger::new(program);
+    let stdin = io::stdin();
+    let mut out = io::stdout();
+
+    writeln!(
+        out,
+        "loaded {} instructions, type `help` for a list of commands",
+        program.len()
+    )?;
+    debugger.print_state(&mut out)?;
+
+    loop {
+        write!(out, "(gb) ")?;
+        out.flush()?;
+
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            break;
+        }
+
+        match debugger.execute(&line, &mut out) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) => writeln!(out, "error: {}", e)?,
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gbcode::EXAMPLE;
+
+    #[test]
+    fn test() {
+        let program = Program::parse(EXAMPLE).unwrap();
+        let mut dbg = Debugger::new(&program);
+        let mut out = vec![];
+
+        assert_eq!(dbg.run(Some(2), true, &mut out).unwrap(), Stop::Stepped);
+        assert_eq!((dbg.process().pc(), dbg.process().acc()), (2, 1));
+
+        dbg.execute("break 4", &mut out).unwrap();
+        assert_eq!(dbg.run(None, true, &mut out).unwrap(), Stop::Breakpoint(4));
+        assert_eq!(dbg.process().acc(), 5);
+
+        assert_eq!(dbg.run(None, true, &mut out).unwrap(), Stop::Loop(4));
+        assert_eq!(dbg.process().acc(), 10);
+        assert_eq!(dbg.run(None, false, &mut out).unwrap(), Stop::Breakpoint(4));
+        assert_eq!(dbg.process().acc(), 15);
+
+        // Without breakpoints, `continue` runs the endless loop until the
+        // budget is spent.
+        let mut dbg = Debugger::new(&program);
+        out.clear();
+        dbg.execute("continue", &mut out).unwrap();
+        assert_eq!(dbg.process().steps(), 1_000_000);
+        assert!(String::from_utf8(out.clone())
+            .unwrap()
+            .contains("after 1000000 instructions\n"));
+
+        let mut dbg = dbg.with_budget(10);
+        dbg.reset();
+        assert_eq!(
+            dbg.run(None, false, &mut out).unwrap(),
+            Stop::BudgetExhausted(7)
+        );
+
+        dbg.reset();
+        dbg.execute("watch", &mut out).unwrap();
+        out.clear();
+        dbg.execute("step 2", &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("acc: 0 -> 1 (at pc 1)"));
+        assert!(out.contains("next: jmp +4"));
+
+        let mut dbg = Debugger::new(&Program::parse("in r1\nout r1").unwrap());
+        assert!(dbg.execute("step", &mut vec![]).is_err());
+        dbg.execute("input 7 8", &mut vec![]).unwrap();
+        dbg.execute("continue", &mut vec![]).unwrap();
+        assert_eq!(dbg.process().output(), [7]);
+
+        // The factorial of the module documentation runs its loop to the end.
+        let factorial = "in r1\nset r2 +1\njz r1 +4\nmul r2 r1\nsub r1 +1\njmp -3\nout r2";
+        let mut dbg = Debugger::new(&Program::parse(factorial).unwrap());
+        dbg.execute("input 5", &mut vec![]).unwrap();
+        dbg.execute("continue", &mut vec![]).unwrap();
+        assert!(dbg.process().done());
+        assert_eq!(dbg.process().output(), [120]);
+
+        let mut dbg = Debugger::new(&Program::parse("nop +0\njmp -2147483648").unwrap());
+        let mut out = vec![];
+        dbg.execute("list 9223372036854775807", &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap().lines().count(), 2);
+        assert!(dbg
+            .execute("list 18446744073709551615", &mut vec![])
+            .is_err());
+        dbg.execute("step 2", &mut vec![]).unwrap();
+        assert_eq!(dbg.process().pc(), -2147483647);
+        dbg.execute("list 10", &mut vec![]).unwrap();
+
+        assert!(dbg.execute("bogus", &mut vec![]).is_err());
+        assert!(!dbg.execute("quit", &mut vec![]).unwrap());
+    }
+}
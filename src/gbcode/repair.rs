@@ -0,0 +1,204 @@
+//! Repairing looping programs by flipping a single `nop` or `jmp`.
+//!
+//! Instead of simulating the program once for every possible flip, this
+//! computes which instructions eventually terminate by walking the reverse
+//! control-flow graph from the end of the program. A flip on the path of the
+//! original run fixes the program if its new successor terminates, which
+//! makes the whole search linear in the length of the program.
+use super::{Instr, Program};
+use crate::common::*;
+use std::fmt;
+
+/// Replacing the instruction at `pc` by `replacement` makes the program
+/// terminate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fix {
+    pub pc: usize,
+    pub replacement: Instr,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RepairError {
+    /// The analysis only supports `nop`, `acc` and `jmp`.
+    Unsupported { pc: usize, instr: Instr },
+    /// The program already terminates without changes.
+    AlreadyTerminates,
+    /// No single flip makes the program terminate.
+    NoFix,
+    /// More than one flip makes the program terminate.
+    Ambiguous(Vec<Fix>),
+}
+
+impl fmt::Display for RepairError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RepairError::Unsupported { pc, instr } => {
+                write!(
+                    f,
+                    "cannot repair programs with {:?} (at pc {})",
+                    instr.to_string(),
+                    pc
+                )
+            }
+            RepairError::AlreadyTerminates => write!(f, "program already terminates"),
+            RepairError::NoFix => write!(f, "no single flip makes the program terminate"),
+            RepairError::Ambiguous(fixes) => {
+                let pcs = fixes.iter().map(|fix| fix.pc).join(", ");
+                write!(
+                    f,
+                    "{} flips make the program terminate, at pcs {}",
+                    fixes.len(),
+                    pcs
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for RepairError {}
+
+/// The successor of `instr` at `pc`, or `None` if it jumps out of bounds.
+fn successor(instr: Instr, pc: usize, len: usize) -> Option<usize> {
+    let next = match instr {
+        Instr::Jmp(off) => pc as i64 + off as i64,
+        _ => pc as i64 + 1,
+    };
+
+    if next >= 0 && next <= len as i64 {
+        Some(next as usize)
+    } else {
+        None
+    }
+}
+
+fn check_supported(program: &Program) -> Result<(), RepairError> {
+    for (pc, &instr) in enumerate(program.instrs()) {
+        if !matches!(instr, Instr::Nop(_) | Instr::Acc(_) | Instr::Jmp(_)) {
+            return Err(RepairError::Unsupported { pc, instr });
+        }
+    }
+
+    Ok(())
+}
+
+/// For every pc, whether execution starting there runs off the end of the
+/// program. The entry at `program.len()` is the end itself.
+pub fn terminating(program: &Program) -> Result<Vec<bool>, RepairError> {
+    check_supported(program)?;
+
+    let n = program.len();
+    let mut preds = vec![vec![]; n + 1];
+
+    for (pc, &instr) in enumerate(program.instrs()) {
+        if let Some(next) = successor(instr, pc, n) {
+            preds[next].push(pc);
+        }
+    }
+
+    let mut result = vec![false; n + 1];
+    let mut stack = vec![n];
+    result[n] = true;
+
+    while let Some(pc) = stack.pop() {
+        for &prev in &preds[pc] {
+            if !result[prev] {
+                result[prev] = true;
+                stack.push(prev);
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// All flips of a single `nop` or `jmp` that make the program terminate.
+pub fn find_fixes(program: &Program) -> Result<Vec<Fix>, RepairError> {
+    let terminates = terminating(program)?;
+    let n = program.len();
+
+    if terminates[0] {
+        return Err(RepairError::AlreadyTerminates);
+    }
+
+    // Only instructions executed by the original run can change its outcome.
+    // Their flipped successors cannot lead back to them: otherwise the
+    // original run would terminate as well.
+    let mut fixes = vec![];
+    let mut visited = vec![false; n];
+    let mut pc = 0;
+
+    while pc < n && !visited[pc] {
+        visited[pc] = true;
+
+        let instr = program[pc];
+        let replacement = match instr {
+            Instr::Nop(v) => Instr::Jmp(v),
+            Instr::Jmp(v) => Instr::Nop(v),
+            _ => instr,
+        };
+
+        if replacement != instr {
+            if let Some(next) = successor(replacement, pc, n) {
+                if terminates[next] {
+                    fixes.push(Fix { pc, replacement });
+                }
+            }
+        }
+
+        pc = match successor(instr, pc, n) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+
+    Ok(fixes)
+}
+
+/// Returns the program with the only flip that makes it terminate.
+pub fn repair(program: &Program) -> Result<Program, RepairError> {
+    let mut fixes = find_fixes(program)?;
+
+    match fixes.len() {
+        0 => Err(RepairError::NoFix),
+        1 => {
+            let fix = fixes.remove(0);
+            let mut program = program.clone();
+            program[fix.pc] = fix.replacement;
+            Ok(program)
+        }
+        _ => Err(RepairError::Ambiguous(fixes)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gbcode::{Outcome, EXAMPLE};
+
+    #[test]
+    fn test_repair() {
+        let program = Program::parse(EXAMPLE).unwrap();
+        let fixed = repair(&program).unwrap();
+
+        assert_eq!(fixed[7], Instr::Nop(-4));
+        assert_eq!(fixed.run().run_to_halt_or_loop(), Outcome::Terminated(8));
+        assert_eq!(repair(&fixed), Err(RepairError::AlreadyTerminates));
+
+        let program = Program::parse("jmp +0\njmp -1").unwrap();
+        assert_eq!(repair(&program), Err(RepairError::NoFix));
+
+        let program = Program::parse("nop +2\njmp -1").unwrap();
+        let fixes = find_fixes(&program).unwrap();
+        assert_eq!(fixes.iter().map(|f| f.pc).collect_vec(), [0, 1]);
+        assert_eq!(
+            repair(&program).unwrap_err().to_string(),
+            "2 flips make the program terminate, at pcs 0, 1"
+        );
+
+        let program = Program::parse("jz acc -1").unwrap();
+        assert!(matches!(
+            repair(&program),
+            Err(RepairError::Unsupported { pc: 0, .. })
+        ));
+    }
+}
@@ -0,0 +1,98 @@
+//! The integer types a process can compute with, and what happens when their
+//! arithmetic overflows.
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+use std::fmt;
+
+/// What arithmetic does when the result does not fit in a word.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Overflow {
+    /// Stop the process with [`Fault::Overflow`](super::Fault::Overflow).
+    #[default]
+    Trap,
+    /// Wrap around in two's complement.
+    Wrap,
+    /// Clamp to the smallest or largest value.
+    Saturate,
+}
+
+/// The type of the registers and memory cells of a process. Zero is the
+/// `Default` value.
+pub trait Word: Clone + fmt::Debug + fmt::Display + Default + Ord + From<i32> {
+    /// Returns `None` if the result overflows and `mode` is [`Overflow::Trap`].
+    fn add_with(&self, rhs: &Self, mode: Overflow) -> Option<Self>;
+    fn sub_with(&self, rhs: &Self, mode: Overflow) -> Option<Self>;
+    fn mul_with(&self, rhs: &Self, mode: Overflow) -> Option<Self>;
+
+    /// The value as a memory address, if it fits in an `i64`.
+    fn to_address(&self) -> Option<i64>;
+}
+
+macro_rules! impl_word_op {
+    ($name:ident, $checked:ident, $wrapping:ident, $saturating:ident) => {
+        fn $name(&self, rhs: &Self, mode: Overflow) -> Option<Self> {
+            match mode {
+                Overflow::Trap => self.$checked(*rhs),
+                Overflow::Wrap => Some(self.$wrapping(*rhs)),
+                Overflow::Saturate => Some(self.$saturating(*rhs)),
+            }
+        }
+    };
+}
+
+macro_rules! impl_word {
+    ($($t:ty),*) => {
+        $(
+            impl Word for $t {
+                impl_word_op!(add_with, checked_add, wrapping_add, saturating_add);
+                impl_word_op!(sub_with, checked_sub, wrapping_sub, saturating_sub);
+                impl_word_op!(mul_with, checked_mul, wrapping_mul, saturating_mul);
+
+                fn to_address(&self) -> Option<i64> {
+                    Some(*self as i64)
+                }
+            }
+        )*
+    };
+}
+
+impl_word!(i32, i64);
+
+/// Big integers never overflow, so the mode is ignored.
+impl Word for BigInt {
+    fn add_with(&self, rhs: &Self, _: Overflow) -> Option<Self> {
+        Some(self + rhs)
+    }
+
+    fn sub_with(&self, rhs: &Self, _: Overflow) -> Option<Self> {
+        Some(self - rhs)
+    }
+
+    fn mul_with(&self, rhs: &Self, _: Overflow) -> Option<Self> {
+        Some(self * rhs)
+    }
+
+    fn to_address(&self) -> Option<i64> {
+        self.to_i64()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_overflow() {
+        use Overflow::*;
+
+        assert_eq!(i32::MAX.add_with(&1, Trap), None);
+        assert_eq!(i32::MAX.add_with(&1, Wrap), Some(i32::MIN));
+        assert_eq!(i32::MAX.add_with(&1, Saturate), Some(i32::MAX));
+        assert_eq!(i64::MIN.sub_with(&1, Saturate), Some(i64::MIN));
+        assert_eq!(3i64.mul_with(&-4, Trap), Some(-12));
+
+        let big = BigInt::from(i64::MAX);
+        assert_eq!(big.mul_with(&big, Trap).unwrap().to_address(), None);
+        assert_eq!(big.to_address(), Some(i64::MAX));
+    }
+}
@@ -0,0 +1,317 @@
+//! Coordinates on a grid of pointy-top hexagons.
+//!
+//! [`Hex`] uses axial coordinates `(q, r)`: `q` grows to the east and `r` to
+//! the south-east. [`Cube`] and "odd-r" offset coordinates (every odd row is
+//! shifted half a hexagon to the east) are available as conversions.
+use crate::automaton::{Coord, Offsets};
+use crate::common::*;
+use std::fmt;
+use std::ops::{Add, Mul, Sub};
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Hex {
+    pub q: i32,
+    pub r: i32,
+}
+
+/// Cube coordinates, where `x + y + z == 0`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Cube {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    E,
+    SE,
+    SW,
+    W,
+    NW,
+    NE,
+}
+
+impl Direction {
+    /// All directions, clockwise starting from east.
+    pub const ALL: [Direction; 6] = [
+        Direction::E,
+        Direction::SE,
+        Direction::SW,
+        Direction::W,
+        Direction::NW,
+        Direction::NE,
+    ];
+
+    /// The neighbor of the origin in this direction.
+    pub fn offset(self) -> Hex {
+        use Direction::*;
+
+        let (q, r) = match self {
+            E => (1, 0),
+            SE => (0, 1),
+            SW => (-1, 1),
+            W => (-1, 0),
+            NW => (0, -1),
+            NE => (1, -1),
+        };
+
+        Hex { q, r }
+    }
+
+    /// Parses a path of directions without separators, like `esenee`.
+    pub fn parse_path(s: &str) -> Result<Vec<Direction>> {
+        let mut path = vec![];
+        let mut rest = s;
+
+        while !rest.is_empty() {
+            let len = if rest.starts_with(&['n', 's'][..]) {
+                2
+            } else {
+                1
+            };
+            let step = rest.get(..len).unwrap_or(rest);
+
+            path.push(step.parse()?);
+            rest = &rest[step.len()..];
+        }
+
+        Ok(path)
+    }
+}
+
+impl FromStr for Direction {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        use Direction::*;
+
+        Ok(match s {
+            "e" => E,
+            "se" => SE,
+            "sw" => SW,
+            "w" => W,
+            "nw" => NW,
+            "ne" => NE,
+            _ => bail!("invalid direction {:?}", s),
+        })
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Direction::*;
+
+        let s = match self {
+            E => "e",
+            SE => "se",
+            SW => "sw",
+            W => "w",
+            NW => "nw",
+            NE => "ne",
+        };
+
+        f.pad(s)
+    }
+}
+
+impl Hex {
+    pub const ORIGIN: Hex = Hex { q: 0, r: 0 };
+
+    pub fn new(q: i32, r: i32) -> Self {
+        Self { q, r }
+    }
+
+    /// The hexagon reached by following `path` from the origin.
+    pub fn from_path(path: &[Direction]) -> Self {
+        path.iter().fold(Hex::ORIGIN, |h, &d| h + d.offset())
+    }
+
+    pub fn neighbor(self, dir: Direction) -> Self {
+        self + dir.offset()
+    }
+
+    pub fn neighbors(self) -> impl Iterator<Item = Hex> {
+        Direction::ALL.iter().map(move |&d| self.neighbor(d))
+    }
+
+    /// The number of steps between two hexagons.
+    pub fn distance(self, other: Hex) -> i32 {
+        let d = (self - other).to_cube();
+        (d.x.abs() + d.y.abs() + d.z.abs()) / 2
+    }
+
+    /// The hexagons at exactly `radius` steps, clockwise starting from the west
+    /// corner.
+    pub fn ring(self, radius: i32) -> Vec<Hex> {
+        if radius <= 0 {
+            return vec![self];
+        }
+
+        let mut result = vec![];
+        let mut hex = self + Direction::W.offset() * radius;
+
+        for &dir in &[
+            Direction::NE,
+            Direction::E,
+            Direction::SE,
+            Direction::SW,
+            Direction::W,
+            Direction::NW,
+        ] {
+            for _ in 0..radius {
+                result.push(hex);
+                hex = hex.neighbor(dir);
+            }
+        }
+
+        result
+    }
+
+    /// The hexagons within `radius` steps, ring by ring from the center.
+    pub fn spiral(self, radius: i32) -> Vec<Hex> {
+        (0..=radius).flat_map(|r| self.ring(r)).collect()
+    }
+
+    pub fn to_cube(self) -> Cube {
+        Cube {
+            x: self.q,
+            y: -self.q - self.r,
+            z: self.r,
+        }
+    }
+
+    pub fn from_cube(c: Cube) -> Self {
+        Self { q: c.x, r: c.z }
+    }
+
+    /// The `(column, row)` in "odd-r" offset coordinates.
+    pub fn to_offset(self) -> (i32, i32) {
+        let col = self.q + (self.r - (self.r & 1)) / 2;
+        (col, self.r)
+    }
+
+    pub fn from_offset(col: i32, row: i32) -> Self {
+        Self {
+            q: col - (row - (row & 1)) / 2,
+            r: row,
+        }
+    }
+}
+
+impl Add for Hex {
+    type Output = Hex;
+
+    fn add(self, other: Hex) -> Hex {
+        Hex::new(self.q + other.q, self.r + other.r)
+    }
+}
+
+impl Sub for Hex {
+    type Output = Hex;
+
+    fn sub(self, other: Hex) -> Hex {
+        Hex::new(self.q - other.q, self.r - other.r)
+    }
+}
+
+impl Mul<i32> for Hex {
+    type Output = Hex;
+
+    fn mul(self, k: i32) -> Hex {
+        Hex::new(self.q * k, self.r * k)
+    }
+}
+
+impl Coord for Hex {
+    fn offset(self, by: Self) -> Self {
+        self + by
+    }
+}
+
+/// The six neighbors of a hexagon, for use with [`crate::automaton::Sparse`].
+pub fn neighborhood() -> Offsets<Hex> {
+    Offsets(Direction::ALL.iter().map(|d| d.offset()).collect())
+}
+
+/// Draws the bounding box of `hexes` as text, with `f` giving the character of
+/// every hexagon. Odd rows are indented by one space, so neighbors touch.
+pub fn render<F>(hexes: impl IntoIterator<Item = Hex>, f: F) -> String
+where
+    F: Fn(Hex) -> char,
+{
+    let offsets = hexes.into_iter().map(Hex::to_offset).collect_vec();
+    let (cols, rows) = match (
+        offsets.iter().map(|o| o.0).minmax().into_option(),
+        offsets.iter().map(|o| o.1).minmax().into_option(),
+    ) {
+        (Some(cols), Some(rows)) => (cols, rows),
+        _ => return String::new(),
+    };
+
+    let mut out = String::new();
+    for row in rows.0..=rows.1 {
+        let mut line = if row & 1 == 1 {
+            " ".to_string()
+        } else {
+            String::new()
+        };
+
+        for col in cols.0..=cols.1 {
+            line.push(f(Hex::from_offset(col, row)));
+            line.push(' ');
+        }
+
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_hex() {
+        let path = Direction::parse_path("nwwswee").unwrap();
+        assert_eq!(path.len(), 5);
+        assert_eq!(Hex::from_path(&path), Hex::ORIGIN);
+        assert_eq!(path.iter().join(","), "nw,w,sw,e,e");
+        assert!(Direction::parse_path("ex").is_err());
+        assert!(Direction::parse_path("n").is_err());
+
+        let h = Hex::from_path(&Direction::parse_path("esew").unwrap());
+        assert_eq!(h, Hex::new(0, 1));
+        assert_eq!(h.distance(Hex::new(3, -2)), 3);
+
+        for &h in &[Hex::new(2, -5), Hex::new(-3, 1), Hex::new(4, 3)] {
+            assert_eq!(Hex::from_cube(h.to_cube()), h);
+            let (col, row) = h.to_offset();
+            assert_eq!(Hex::from_offset(col, row), h);
+        }
+
+        assert_eq!(Hex::new(-1, 1).to_offset(), (-1, 1));
+        assert_eq!(Hex::new(0, 1).to_offset(), (0, 1));
+
+        let ring = Hex::ORIGIN.ring(2);
+        assert_eq!(ring.len(), 12);
+        assert!(all(&ring, |h| h.distance(Hex::ORIGIN) == 2));
+        assert_eq!(Hex::ORIGIN.spiral(2).len(), 19);
+        assert_eq!(Hex::ORIGIN.spiral(1)[1..], Hex::ORIGIN.ring(1)[..]);
+    }
+
+    #[test]
+    fn test_render() {
+        // The bounding box has a spare corner on the odd rows.
+        let text = render(Hex::ORIGIN.spiral(1), |h| match h.distance(Hex::ORIGIN) {
+            0 => '#',
+            1 => '.',
+            _ => ' ',
+        });
+        assert_eq!(text, " . .\n. # .\n . .\n");
+        assert_eq!(render(vec![], |_| '#'), "");
+    }
+}
@@ -0,0 +1,115 @@
+//! Solutions for Advent of Code 2020.
+//!
+//! Every day is a module with a type that implements [`common::Solver`], which
+//! parses the puzzle input and solves both parts. [`common::run`] reads the
+//! input of a day from `src/inputs` and returns the answers as a
+//! [`common::Solution`]. The [`DAYS`] table gives access to all days by number.
+
+// `recap` derives its trait impls inside a const block.
+#![allow(non_local_definitions)]
+// Style lints that the original solutions predate.
+#![allow(
+    clippy::bool_assert_comparison,
+    clippy::borrow_deref_ref,
+    clippy::filter_next,
+    clippy::len_zero,
+    clippy::needless_range_loop,
+    clippy::needless_return,
+    clippy::useless_conversion,
+    clippy::useless_vec,
+    redundant_semicolons
+)]
+
+pub mod answers;
+pub mod automaton;
+pub mod common;
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+pub mod gbcode;
+pub mod hex;
+pub mod pattern;
+pub mod render;
+pub mod report;
+pub mod runner;
+pub mod timing;
+
+use common::{bail, run, Parts, Result, Solution};
+use timing::{measure, Timings};
+
+/// Reads the input of a day and solves the selected parts, see [`common::run`].
+pub type RunFn = fn(Parts) -> Result<Solution>;
+
+/// Reads the input of a day and times its phases a number of times, see
+/// [`timing::measure`].
+pub type MeasureFn = fn(Parts, usize) -> Result<(Solution, Vec<Timings>)>;
+
+/// Builds the tables of all days from a single list of solvers, so that they
+/// cannot disagree.
+macro_rules! days {
+    ($($day:ident::$solver:ident,)*) => {
+        /// The solver of every day, where `DAYS[0]` is day 1.
+        pub const DAYS: [RunFn; 25] = [$(run::<$day::$solver>,)*];
+
+        /// The timed solver of every day, in the same order as [`DAYS`].
+        pub const MEASURES: [MeasureFn; 25] = [$(measure::<$day::$solver>,)*];
+    };
+}
+
+days! {
+    day01::Day01,
+    day02::Day02,
+    day03::Day03,
+    day04::Day04,
+    day05::Day05,
+    day06::Day06,
+    day07::Day07,
+    day08::Day08,
+    day09::Day09,
+    day10::Day10,
+    day11::Day11,
+    day12::Day12,
+    day13::Day13,
+    day14::Day14,
+    day15::Day15,
+    day16::Day16,
+    day17::Day17,
+    day18::Day18,
+    day19::Day19,
+    day20::Day20,
+    day21::Day21,
+    day22::Day22,
+    day23::Day23,
+    day24::Day24,
+    day25::Day25,
+}
+
+/// Solves the given parts of the puzzle of the given day (starting at 1).
+pub fn solve(day: usize, parts: Parts) -> Result<Solution> {
+    match day.checked_sub(1).and_then(|i| DAYS.get(i)) {
+        Some(run) => run(parts),
+        None => bail!("day must be a number between 1 and {}", DAYS.len()),
+    }
+}
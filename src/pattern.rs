@@ -0,0 +1,160 @@
+//! Searching a grid of characters for ASCII drawings in any orientation.
+//!
+//! A [`Template`] is a drawing where `#` cells must match a `#` in the target
+//! and every other character matches anything. [`search`] looks for several
+//! templates at once, in all eight rotations and reflections, and reports
+//! overlapping matches separately.
+use crate::common::*;
+use ndarray::prelude::*;
+
+/// A drawing to search for, in every distinct orientation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Template {
+    /// The `#` cells of every distinct orientation, with the first transform
+    /// that produces it.
+    orientations: Vec<(Transform, Vec<[usize; 2]>)>,
+}
+
+impl Template {
+    /// Parses a drawing, where lines may have different lengths and blank
+    /// margins are ignored.
+    pub fn parse(drawing: &str) -> Result<Self> {
+        let lines = drawing.lines().collect_vec();
+        let cols = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let mut cells = Array2::from_elem((lines.len(), cols), false);
+
+        for (i, line) in enumerate(&lines) {
+            for (j, c) in enumerate(line.chars()) {
+                cells[[i, j]] = c == '#';
+            }
+        }
+
+        if !any(&cells, |&c| c) {
+            bail!("template has no '#' cells");
+        }
+
+        let mut orientations: Vec<(Transform, Vec<[usize; 2]>)> = vec![];
+        for &t in &Transform::ALL {
+            let mut spots = t
+                .apply(cells.view())
+                .indexed_iter()
+                .filter(|&(_, &c)| c)
+                .map(|((i, j), _)| [i, j])
+                .collect_vec();
+
+            // Blank lines and columns around the drawing do not count.
+            let top = spots.iter().map(|p| p[0]).min().unwrap_or(0);
+            let left = spots.iter().map(|p| p[1]).min().unwrap_or(0);
+            for p in &mut spots {
+                *p = [p[0] - top, p[1] - left];
+            }
+
+            if !orientations.iter().any(|(_, other)| *other == spots) {
+                orientations.push((t, spots));
+            }
+        }
+
+        Ok(Self { orientations })
+    }
+
+    /// The number of distinct orientations, between one and eight.
+    pub fn orientations(&self) -> usize {
+        self.orientations.len()
+    }
+}
+
+/// A template found in the target.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Match {
+    /// Index of the template in the list given to [`search`].
+    pub template: usize,
+    /// How the template is rotated or flipped. Symmetric templates only
+    /// report the first of several equivalent transforms.
+    pub orientation: Transform,
+    /// The top left corner of the bounding box of the match.
+    pub pos: [usize; 2],
+    /// The matched cells of the target.
+    pub cells: Vec<[usize; 2]>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Search {
+    pub matches: Vec<Match>,
+    /// The number of `#` cells of the target outside of every match.
+    pub leftover: usize,
+}
+
+/// Finds every occurrence of the templates in `target`, in row-major order of
+/// their position.
+pub fn search(target: ArrayView2<char>, templates: &[Template]) -> Search {
+    let mut matches = vec![];
+    let mut covered = Array2::from_elem(target.dim(), false);
+
+    for ((i, j), _) in target.indexed_iter() {
+        for (t, template) in enumerate(templates) {
+            for (orientation, spots) in &template.orientations {
+                let cells = spots.iter().map(|&[di, dj]| [i + di, j + dj]).collect_vec();
+
+                if all(&cells, |&p| target.get(p) == Some(&'#')) {
+                    for &p in &cells {
+                        covered[p] = true;
+                    }
+
+                    matches.push(Match {
+                        template: t,
+                        orientation: *orientation,
+                        pos: [i, j],
+                        cells,
+                    });
+                }
+            }
+        }
+    }
+
+    let leftover = target
+        .iter()
+        .zip(&covered)
+        .filter(|&(&c, &covered)| c == '#' && !covered)
+        .count();
+
+    Search { matches, leftover }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_search() {
+        let target = Grid::parse(
+            "\
+#.#...
+###..#
+...###
+.#.#..",
+        )
+        .unwrap()
+        .into_array();
+
+        let corner = Template::parse("\n #\n ##").unwrap();
+        let plus = Template::parse(" #\n###\n #").unwrap();
+        assert_eq!(corner.orientations(), 4);
+        assert_eq!(plus.orientations(), 1);
+        assert_eq!(Template::parse("\n  #\n  #").unwrap().orientations(), 2);
+        assert!(Template::parse("  \n.").is_err());
+
+        let result = search(target.view(), &[corner, plus]);
+        let found = result
+            .matches
+            .iter()
+            .map(|m| (m.pos, m.cells.len()))
+            .collect_vec();
+        assert_eq!(found, [([0, 0], 3), ([0, 1], 3), ([1, 4], 3), ([2, 3], 3)]);
+
+        // The first two matches overlap, and only one '#' is not covered.
+        assert_eq!(result.matches[0].cells[2], [1, 1]);
+        assert_eq!(result.matches[1].cells[1], [1, 1]);
+        assert!(all(&result.matches, |m| m.template == 0));
+        assert_eq!(result.leftover, 1);
+    }
+}
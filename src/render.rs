@@ -0,0 +1,256 @@
+//! Drawing grids of characters as PPM or PNG images.
+//!
+//! Every character becomes a square of `scale` by `scale` pixels in the color
+//! given by a [`Palette`]. Automata are saved as numbered frames, one image
+//! per generation, see [`save_frames`].
+use crate::common::*;
+use ndarray::prelude::*;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+pub type Rgb = [u8; 3];
+
+/// The color of every character.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Palette {
+    colors: HashMap<char, Rgb>,
+    background: Rgb,
+    scale: usize,
+}
+
+impl Palette {
+    /// A palette where every character has the `background` color.
+    pub fn new(background: Rgb) -> Self {
+        Self {
+            colors: default(),
+            background,
+            scale: 1,
+        }
+    }
+
+    pub fn with(mut self, c: char, color: Rgb) -> Self {
+        self.colors.insert(c, color);
+        self
+    }
+
+    /// Draws every character as `scale` by `scale` pixels.
+    pub fn with_scale(mut self, scale: usize) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    pub fn color(&self, c: char) -> Rgb {
+        self.colors.get(&c).copied().unwrap_or(self.background)
+    }
+
+    /// Sets a color given as `C=RRGGBB`, like `#=ff8000`.
+    pub fn set(&mut self, spec: &str) -> Result {
+        let mut chars = spec.chars();
+
+        match (chars.next(), chars.next()) {
+            (Some(c), Some('=')) => {
+                self.colors.insert(c, parse_color(chars.as_str())?);
+                Ok(())
+            }
+            _ => bail!("expecting a color like \"#=ff8000\", found {:?}", spec),
+        }
+    }
+}
+
+/// Dark `#`, light grey `L`, red `O` and white everything else.
+impl Default for Palette {
+    fn default() -> Self {
+        Self::new([0xff, 0xff, 0xff])
+            .with('#', [0x20, 0x20, 0x30])
+            .with('L', [0xb0, 0xb0, 0xb0])
+            .with('O', [0xe0, 0x30, 0x30])
+            .with_scale(4)
+    }
+}
+
+/// Parses a color in hexadecimal, like `ff8000`.
+pub fn parse_color(s: &str) -> Result<Rgb> {
+    let s = s.strip_prefix('#').unwrap_or(s);
+    let value = match s.len() {
+        6 if s.chars().all(|c| c.is_ascii_hexdigit()) => u32::from_str_radix(s, 16).ok(),
+        _ => None,
+    };
+
+    let value = value.ok_or_else(|| anyhow!("invalid color {:?}", s))?;
+    Ok([(value >> 16) as u8, (value >> 8) as u8, value as u8])
+}
+
+/// An RGB image with eight bits per channel.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn from_chars(cells: ArrayView2<char>, palette: &Palette) -> Self {
+        let scale = palette.scale;
+        let (rows, cols) = cells.dim();
+        let mut pixels = Vec::with_capacity(rows * cols * scale * scale * 3);
+
+        for row in cells.genrows() {
+            for _ in 0..scale {
+                for &c in row {
+                    for _ in 0..scale {
+                        pixels.extend_from_slice(&palette.color(c));
+                    }
+                }
+            }
+        }
+
+        Self {
+            width: cols * scale,
+            height: rows * scale,
+            pixels,
+        }
+    }
+
+    /// Draws one row per line. Shorter lines are padded with spaces.
+    pub fn from_text(text: &str, palette: &Palette) -> Self {
+        let lines = text.lines().collect_vec();
+        let cols = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let mut cells = Array2::from_elem((lines.len(), cols), ' ');
+
+        for (i, line) in enumerate(&lines) {
+            for (j, c) in enumerate(line.chars()) {
+                cells[[i, j]] = c;
+            }
+        }
+
+        Self::from_chars(cells.view(), palette)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Rgb {
+        let i = (y * self.width + x) * 3;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2]]
+    }
+
+    /// Writes a binary PPM (`P6`) image.
+    pub fn write_ppm(&self, mut w: impl Write) -> Result {
+        write!(w, "P6\n{} {}\n255\n", self.width, self.height)?;
+        w.write_all(&self.pixels)?;
+        Ok(())
+    }
+
+    pub fn write_png(&self, w: impl Write) -> Result {
+        let mut encoder = png::Encoder::new(w, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        Ok(())
+    }
+
+    /// Writes a PPM or PNG image, depending on the extension of `path`.
+    pub fn save(&self, path: &Path) -> Result {
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let png = match &*ext.to_ascii_lowercase() {
+            "png" => true,
+            "ppm" => false,
+            _ => bail!("unknown image format {:?}, expecting .png or .ppm", ext),
+        };
+
+        let file = File::create(path).with_context(|| format!("failed to create {:?}", path))?;
+        let mut w = BufWriter::new(file);
+
+        if png {
+            self.write_png(&mut w)?;
+        } else {
+            self.write_ppm(&mut w)?;
+        }
+
+        w.flush()?;
+        Ok(())
+    }
+}
+
+/// The path of frame `index`, numbered before the extension: `day24.png`
+/// becomes `day24-007.png`.
+pub fn frame_path(path: &Path, index: usize) -> PathBuf {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+    let mut name = format!("{}-{:03}", stem, index);
+
+    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+        name = format!("{}.{}", name, ext);
+    }
+
+    path.with_file_name(name)
+}
+
+/// Creates the directory of `path` and its parents, if needed.
+pub fn create_parent_dir(path: &Path) -> Result {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create {:?}", dir))?;
+    }
+
+    Ok(())
+}
+
+/// Saves every frame as a numbered image next to `path`, creating its
+/// directory if needed, and returns the number of frames.
+pub fn save_frames(path: &Path, frames: impl IntoIterator<Item = Image>) -> Result<usize> {
+    create_parent_dir(path)?;
+    let mut count = 0;
+
+    for (i, frame) in enumerate(frames) {
+        frame.save(&frame_path(path, i))?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_image() {
+        let palette = Palette::new([0, 0, 0]).with('#', [255, 0, 0]).with_scale(2);
+        let image = Image::from_text("#.\n.#\n#", &palette);
+        assert_eq!((image.width(), image.height()), (4, 6));
+        assert_eq!(image.pixel(1, 1), [255, 0, 0]);
+        assert_eq!(image.pixel(2, 1), [0, 0, 0]);
+        assert_eq!(image.pixel(3, 5), [0, 0, 0]);
+
+        let mut ppm = vec![];
+        image.write_ppm(&mut ppm).unwrap();
+        assert!(ppm.starts_with(b"P6\n4 6\n255\n"));
+        assert_eq!(ppm.len(), 11 + 4 * 6 * 3);
+
+        let mut png = vec![];
+        image.write_png(&mut png).unwrap();
+        let mut reader = png::Decoder::new(&png[..]).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut pixels).unwrap();
+        assert_eq!(pixels, image.pixels);
+
+        let mut palette = Palette::default();
+        palette.set("#=#00ff80").unwrap();
+        assert_eq!(palette.color('#'), [0, 255, 128]);
+        assert!(palette.set("#00ff80").is_err());
+        assert!(palette.set("#=00ff8").is_err());
+        assert!(parse_color("+12345").is_err());
+        assert!(parse_color("-00001").is_err());
+
+        let path = Path::new("out/day24.png");
+        assert_eq!(frame_path(path, 7), Path::new("out/day24-007.png"));
+        assert!(image.save(Path::new("day24.gif")).is_err());
+    }
+}
@@ -0,0 +1,209 @@
+//! The results of the runner and their output as JSON or CSV records.
+
+use crate::answers::Mismatch;
+use crate::common::*;
+use crate::timing::{Stats, Timings};
+use serde::Serialize;
+use std::io::Write;
+use std::str::FromStr;
+
+/// Output format of the runner.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "text" => Format::Text,
+            "json" => Format::Json,
+            "csv" => Format::Csv,
+            _ => bail!("format must be `text`, `json` or `csv`, found {:?}", s),
+        })
+    }
+}
+
+/// The outcome of running a single day.
+pub struct DayResult {
+    pub day: usize,
+    pub solution: Result<Solution>,
+    pub mismatches: Vec<Mismatch>,
+    pub timings: Vec<Timings>,
+}
+
+/// One part of one day in machine-readable form. The time is the median
+/// number of seconds spent on the part, if timing was enabled.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Record {
+    pub day: usize,
+    pub part: String,
+    pub answer: Option<String>,
+    pub time: Option<f64>,
+    pub error: Option<String>,
+}
+
+pub fn records(results: &[DayResult], parts: Parts) -> Vec<Record> {
+    let mut records = vec![];
+
+    for r in results {
+        for &part in &Part::ALL {
+            if !parts.contains(part) {
+                continue;
+            }
+
+            let durations = r.timings.iter().filter_map(|t| match part {
+                Part::A => t.part_a,
+                Part::B => t.part_b,
+            });
+
+            let mut record = Record {
+                day: r.day,
+                part: part.to_string(),
+                answer: None,
+                time: Stats::new(durations).map(|s| s.median.as_secs_f64()),
+                error: None,
+            };
+
+            match &r.solution {
+                Ok(solution) => {
+                    record.answer = solution.get(part).map(|a| a.to_string());
+
+                    if let Some(m) = r.mismatches.iter().find(|m| m.part == part) {
+                        record.error = Some(format!("wrong answer, expected {}", m.expected));
+                    }
+                }
+                Err(e) => {
+                    record.error = Some(format!("{:#}", e));
+                }
+            }
+
+            records.push(record);
+        }
+    }
+
+    records
+}
+
+pub fn write_json(out: &mut impl Write, records: &[Record]) -> Result {
+    serde_json::to_writer_pretty(&mut *out, records)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+pub fn write_csv(out: &mut impl Write, records: &[Record]) -> Result {
+    writeln!(out, "day,part,answer,time,error")?;
+
+    for r in records {
+        writeln!(
+            out,
+            "{},{},{},{},{}",
+            r.day,
+            r.part,
+            csv_field(r.answer.as_deref().unwrap_or_default()),
+            r.time.map(|t| t.to_string()).unwrap_or_default(),
+            csv_field(r.error.as_deref().unwrap_or_default()),
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    fn results() -> Vec<DayResult> {
+        vec![
+            DayResult {
+                day: 1,
+                solution: Ok(Solution {
+                    part_a: Some(12.into()),
+                    part_b: Some("a,b".into()),
+                }),
+                mismatches: vec![Mismatch {
+                    part: Part::B,
+                    expected: "a".into(),
+                    actual: Some("a,b".into()),
+                }],
+                timings: vec![Timings {
+                    parse: Duration::from_millis(1),
+                    part_a: Some(Duration::from_millis(250)),
+                    part_b: Some(Duration::from_millis(500)),
+                }],
+            },
+            DayResult {
+                day: 2,
+                solution: Err(anyhow!("failed to open \"day02\"")),
+                mismatches: vec![],
+                timings: vec![],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_records() {
+        let records = records(&results(), Parts::BOTH);
+
+        assert_eq!(records.len(), 4);
+        assert_eq!(
+            records[0],
+            Record {
+                day: 1,
+                part: "A".into(),
+                answer: Some("12".into()),
+                time: Some(0.25),
+                error: None,
+            }
+        );
+        assert_eq!(
+            records[1].error.as_deref(),
+            Some("wrong answer, expected a")
+        );
+        assert_eq!(records[3].answer, None);
+        assert_eq!(records[3].time, None);
+
+        let records = super::records(&results(), Parts { a: false, b: true });
+        assert_eq!(records.len(), 2);
+    }
+
+    #[test]
+    fn test_csv() {
+        let mut out = vec![];
+        write_csv(&mut out, &records(&results(), Parts::BOTH)).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "day,part,answer,time,error\n\
+             1,A,12,0.25,\n\
+             1,B,\"a,b\",0.5,\"wrong answer, expected a\"\n\
+             2,A,,,\"failed to open \"\"day02\"\"\"\n\
+             2,B,,,\"failed to open \"\"day02\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let mut out = vec![];
+        write_json(&mut out, &records(&results(), Parts { a: true, b: false })).unwrap();
+
+        let value: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(value[0]["day"], 1);
+        assert_eq!(value[0]["answer"], "12");
+        assert_eq!(value[1]["answer"], serde_json::Value::Null);
+        assert_eq!(value[1]["error"], "failed to open \"day02\"");
+    }
+}
@@ -0,0 +1,568 @@
+use crate::answers::{Manifest, Mismatch};
+use crate::common::*;
+use crate::day11::{Day11, SeatRules};
+use crate::day17::{self, Day17};
+use crate::day20::{self, Day20};
+use crate::day24::{self, Day24};
+use crate::gbcode::cfg::Cfg;
+use crate::gbcode::{debugger, Program};
+use crate::render::{create_parent_dir, frame_path, save_frames, Image, Palette};
+use crate::report::{records, write_csv, write_json, DayResult, Format};
+use crate::timing::{FormatDuration, Stats, Timings, PHASES};
+use crate::{DAYS, MEASURES};
+use std::collections::BTreeSet;
+use std::env;
+use std::io::stdout;
+use std::iter::once;
+use std::path::{Path, PathBuf};
+
+const USAGE: &str = "\
+usage: {binary} [options] <days>...
+       {binary} debug|disasm|cfg [PATH]
+       {binary} render <day> PATH [--color C=RRGGBB]... [--scale N]
+
+Days can be given as a single day (`8`), a range (`3-7`), a comma-separated
+list (`1,3,5-7`) or `all`.
+
+options:
+  --part a|b          only solve the given part
+  --input-dir DIR     read inputs from DIR (default: $AOC_INPUT_DIR or src/inputs)
+  --input [DAY=]PATH  read the input of DAY from PATH, or from stdin if PATH is `-`.
+                      DAY may be omitted if only a single day is selected.
+  --check             compare the answers against the known-good answers
+  --answers PATH      file with known-good answers (default: answers.toml)
+  --time              report the time spent parsing and solving each part
+  --bench N           like `--time`, but repeat every day N times
+  --format FORMAT     output `text` (default), `json` or `csv`
+
+The other commands work on the handheld program at PATH (default: the input
+of day 8). `debug` starts an interactive debugger, `disasm` prints an annotated
+listing, and `cfg` prints the control-flow graph in Graphviz DOT format.
+
+`render` draws day 20 as a PNG or PPM image at PATH, depending on its
+extension. For days 11, 17 and 24 it draws every generation as a numbered frame
+next to PATH, like `day24-000.png`. `--color` sets the color of a character and
+`--scale` the number of pixels per character. `--input-dir` and `--input`
+select the input like they do for solving.";
+
+#[derive(Debug, PartialEq, Eq)]
+struct Options {
+    days: BTreeSet<usize>,
+    parts: Parts,
+    input_dir: Option<PathBuf>,
+    inputs: Vec<(usize, InputSource)>,
+    check: bool,
+    answers: Option<PathBuf>,
+    timing_runs: usize,
+    format: Format,
+}
+
+fn parse_day(s: &str) -> Result<usize> {
+    let day = s
+        .trim()
+        .parse::<usize>()
+        .map_err(|_| anyhow!("invalid day {:?}", s))?;
+
+    if day == 0 || day > DAYS.len() {
+        bail!("day must be a number between 1 and {}", DAYS.len());
+    }
+
+    Ok(day)
+}
+
+fn parse_days(spec: &str, days: &mut BTreeSet<usize>) -> Result {
+    for item in spec.split(',') {
+        if item == "all" {
+            days.extend(1..=DAYS.len());
+        } else if let Some((begin, end)) = item.split_once('-') {
+            let (begin, end) = (parse_day(begin)?, parse_day(end)?);
+
+            if begin > end {
+                bail!("invalid range {:?}", item);
+            }
+
+            days.extend(begin..=end);
+        } else {
+            days.insert(parse_day(item)?);
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_part(s: &str) -> Result<Parts> {
+    Ok(match &*s.to_ascii_lowercase() {
+        "a" => Parts { a: true, b: false },
+        "b" => Parts { a: false, b: true },
+        _ => bail!("part must be either `a` or `b`, found {:?}", s),
+    })
+}
+
+/// Returns the value of option `name` if `arg` is that option, given either as
+/// `--name value` or as `--name=value`.
+fn option_value<'a, I>(name: &str, arg: &'a str, args: &mut I) -> Result<Option<&'a str>>
+where
+    I: Iterator<Item = &'a String>,
+{
+    match arg.strip_prefix(name) {
+        Some("") => match args.next() {
+            Some(value) => Ok(Some(value)),
+            None => bail!("missing value for `{}`", name),
+        },
+        Some(rest) => Ok(rest.strip_prefix('=')),
+        None => Ok(None),
+    }
+}
+
+/// Parses the value of `--input`, which may omit the day if only a single day
+/// is selected.
+fn parse_input(value: &str, days: &BTreeSet<usize>) -> Result<(usize, InputSource)> {
+    let (day, path) = match value.split_once('=') {
+        Some((day, path)) if day.parse::<usize>().is_ok() => (parse_day(day)?, path),
+        _ if days.len() == 1 => (*days.iter().next().unwrap(), value),
+        _ => bail!(
+            "`--input {}` needs a day when multiple days are selected",
+            value
+        ),
+    };
+
+    Ok((day, InputSource::parse(path)))
+}
+
+/// Reads inputs from the directory and files given on the command line.
+fn set_inputs(input_dir: &Option<PathBuf>, inputs: &[(usize, InputSource)]) {
+    if let Some(dir) = input_dir {
+        set_input_dir(dir);
+    }
+
+    for (day, source) in inputs {
+        set_input_source(&format!("day{:02}", day), source.clone());
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<Options> {
+    let mut days = BTreeSet::new();
+    let mut parts = Parts::BOTH;
+    let mut input_dir = None;
+    let mut raw_inputs = vec![];
+    let mut check = false;
+    let mut answers = None;
+    let mut timing_runs = 0;
+    let mut format = Format::Text;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        if let Some(value) = option_value("--part", arg, &mut args)? {
+            parts = parse_part(value)?;
+        } else if let Some(value) = option_value("--input-dir", arg, &mut args)? {
+            input_dir = Some(PathBuf::from(value));
+        } else if let Some(value) = option_value("--input", arg, &mut args)? {
+            raw_inputs.push(value);
+        } else if let Some(value) = option_value("--answers", arg, &mut args)? {
+            answers = Some(PathBuf::from(value));
+        } else if let Some(value) = option_value("--bench", arg, &mut args)? {
+            timing_runs = match value.parse() {
+                Ok(n) if n > 0 => n,
+                _ => bail!(
+                    "number of runs must be a positive number, found {:?}",
+                    value
+                ),
+            };
+        } else if let Some(value) = option_value("--format", arg, &mut args)? {
+            format = value.parse()?;
+        } else if arg == "--time" {
+            timing_runs = usize::max(timing_runs, 1);
+        } else if arg == "--check" {
+            check = true;
+        } else if arg.starts_with('-') {
+            bail!("unknown option {:?}", arg);
+        } else {
+            parse_days(arg, &mut days)?;
+        }
+    }
+
+    if days.is_empty() {
+        bail!("no days given");
+    }
+
+    let inputs = raw_inputs
+        .into_iter()
+        .map(|value| parse_input(value, &days))
+        .collect::<Result<_>>()?;
+
+    Ok(Options {
+        days,
+        parts,
+        input_dir,
+        inputs,
+        check,
+        answers,
+        timing_runs,
+        format,
+    })
+}
+
+fn print_day(r: &DayResult) {
+    println!("=== day {:02} ===", r.day);
+
+    match &r.solution {
+        Ok(solution) => {
+            print_solution(solution);
+            print_mismatches(&r.mismatches);
+            print_timings(&r.timings);
+        }
+        Err(e) => println!("error: {:?}", e),
+    }
+
+    println!();
+}
+
+fn print_solution(solution: &Solution) {
+    for (name, answer) in &[("A", &solution.part_a), ("B", &solution.part_b)] {
+        if let Some(answer) = answer {
+            println!("part {}: {}", name, answer);
+        }
+    }
+}
+
+fn print_mismatches(mismatches: &[Mismatch]) {
+    for m in mismatches {
+        match &m.actual {
+            Some(actual) => println!(
+                "part {}: wrong answer, expected {} but found {}",
+                m.part, m.expected, actual
+            ),
+            None => println!("part {}: no answer, expected {}", m.part, m.expected),
+        }
+    }
+}
+
+fn print_timings(timings: &[Timings]) {
+    for (i, name) in enumerate(&PHASES) {
+        if let Some(stats) = Stats::new(timings.iter().filter_map(|t| t.phases()[i])) {
+            if timings.len() == 1 {
+                println!(
+                    "{:<7} {}",
+                    format!("{}:", name),
+                    FormatDuration(stats.median)
+                );
+            } else {
+                println!("{:<7} {}", format!("{}:", name), stats);
+            }
+        }
+    }
+}
+
+fn print_summary(results: &[DayResult], timing: bool) {
+    println!("summary:");
+
+    if timing {
+        println!(" day |      parse |     part A |     part B |      total | result");
+        println!("-----+------------+------------+------------+------------+--------");
+    } else {
+        println!(" day | result");
+        println!("-----+--------");
+    }
+
+    for r in results {
+        print!("  {:02} | ", r.day);
+
+        if timing {
+            for i in 0..PHASES.len() {
+                match Stats::new(r.timings.iter().filter_map(|t| t.phases()[i])) {
+                    Some(stats) => print!("{:>10} | ", FormatDuration(stats.median)),
+                    None => print!("{:>10} | ", "-"),
+                }
+            }
+        }
+
+        match &r.solution {
+            Err(e) => println!("error: {:#}", e),
+            Ok(_) if !r.mismatches.is_empty() => println!(
+                "wrong answer for part {}",
+                r.mismatches.iter().map(|m| m.part).join(" and ")
+            ),
+            Ok(_) => println!("ok"),
+        }
+    }
+}
+
+/// Runs `debug`, `disasm` or `cfg` on the program at the path in `args`.
+fn run_gbcode(cmd: &str, args: &[String]) -> Result {
+    let program = match args {
+        [] => Program::load(None)?,
+        [path] => Program::load(Some(path))?,
+        _ => bail!("usage: {} [PATH]", cmd),
+    };
+
+    match cmd {
+        "debug" => debugger::main(&program)?,
+        "disasm" => print!("{}", Cfg::new(&program).listing(&program)),
+        _ => print!("{}", Cfg::new(&program).to_dot(&program)),
+    }
+
+    Ok(())
+}
+
+/// Draws the sea monsters of day 20, or the generations of the automata of
+/// days 11, 17 and 24.
+fn run_render(args: &[String]) -> Result {
+    let mut palette = Palette::default();
+    let mut input_dir = None;
+    let mut raw_inputs = vec![];
+    let mut positional = vec![];
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        if let Some(value) = option_value("--input-dir", arg, &mut args)? {
+            input_dir = Some(PathBuf::from(value));
+        } else if let Some(value) = option_value("--input", arg, &mut args)? {
+            raw_inputs.push(value);
+        } else if let Some(spec) = option_value("--color", arg, &mut args)? {
+            palette.set(spec)?;
+        } else if let Some(scale) = option_value("--scale", arg, &mut args)? {
+            let scale = scale
+                .parse()
+                .map_err(|_| anyhow!("invalid scale {:?}", scale))?;
+            palette = palette.with_scale(scale);
+        } else {
+            positional.push(arg);
+        }
+    }
+
+    let (day, path) = match &*positional {
+        [day, path] => (parse_day(day)?, Path::new(path)),
+        _ => bail!("usage: render <day> PATH [--color C=RRGGBB]... [--scale N]"),
+    };
+
+    let days = once(day).collect();
+    let inputs = raw_inputs
+        .into_iter()
+        .map(|value| parse_input(value, &days))
+        .collect::<Result<Vec<_>>>()?;
+    set_inputs(&input_dir, &inputs);
+
+    let input = read_input_string(&format!("day{:02}", day))?;
+    let frames = match day {
+        11 => {
+            let rules = SeatRules::part_a();
+            let grid = Day11::parse(&input)?;
+
+            once(grid.clone())
+                .chain(rules.rounds(&grid))
+                .map(|g| rules.render(&g))
+                .collect()
+        }
+        17 => day17::slices(&Day17::parse(&input)?),
+        20 => {
+            let (ids, maps) = Day20::parse(&input)?;
+            let map = day20::sea_monster_map(&ids, &maps)?;
+
+            create_parent_dir(path)?;
+            Image::from_chars(map.view(), &palette).save(path)?;
+            println!("wrote {}", path.display());
+            return Ok(());
+        }
+        24 => day24::floor(&Day24::parse(&input)?, 100),
+        _ => bail!("cannot render day {}, only days 11, 17, 20 and 24", day),
+    };
+
+    let images = frames.iter().map(|f| Image::from_text(f, &palette));
+    let count = save_frames(path, images)?;
+    println!(
+        "wrote {} frames to {}",
+        count,
+        frame_path(path, 0).display()
+    );
+
+    Ok(())
+}
+
+/// Entry point of the command line runner.
+pub fn main() -> Result {
+    let mut args = env::args();
+    let binary = args.next().unwrap_or_default();
+    let args: Vec<_> = args.collect();
+
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{}", USAGE.replace("{binary}", &binary));
+        return Ok(());
+    }
+
+    if let Some(cmd @ ("debug" | "disasm" | "cfg")) = args.first().map(|s| &**s) {
+        return run_gbcode(cmd, &args[1..]);
+    }
+
+    if args.first().map(|s| &**s) == Some("render") {
+        return run_render(&args[1..]);
+    }
+
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", USAGE.replace("{binary}", &binary));
+            return Err(e);
+        }
+    };
+
+    set_inputs(&options.input_dir, &options.inputs);
+
+    if options.timing_runs > 0 && cfg!(debug_assertions) {
+        eprintln!("warning: timing a debug build, use `--release` for meaningful results");
+    }
+
+    let manifest = if options.check {
+        let default_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("answers.toml");
+        Manifest::load(options.answers.as_deref().unwrap_or(&default_path))?
+    } else {
+        Manifest::default()
+    };
+
+    let mut results = vec![];
+    for &day in &options.days {
+        let run = DAYS[day - 1];
+        let mut mismatches = vec![];
+        let mut timings = vec![];
+
+        let solution = if options.timing_runs > 0 {
+            MEASURES[day - 1](options.parts, options.timing_runs).map(|(solution, t)| {
+                timings = t;
+                solution
+            })
+        } else {
+            run(options.parts)
+        };
+
+        if let Ok(solution) = &solution {
+            mismatches = manifest.check(day, options.parts, solution);
+        }
+
+        let result = DayResult {
+            day,
+            solution,
+            mismatches,
+            timings,
+        };
+
+        if options.format == Format::Text {
+            print_day(&result);
+        }
+
+        results.push(result);
+    }
+
+    match options.format {
+        Format::Text => print_summary(&results, options.timing_runs > 0),
+        Format::Json => write_json(&mut stdout(), &records(&results, options.parts))?,
+        Format::Csv => write_csv(&mut stdout(), &records(&results, options.parts))?,
+    }
+
+    let failed = results
+        .iter()
+        .filter(|r| r.solution.is_err() || !r.mismatches.is_empty())
+        .count();
+
+    if failed > 0 {
+        bail!("{} out of {} days failed", failed, results.len());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_days() {
+        let options = parse_args(&args(&["3-5,1", "8"])).unwrap();
+        assert_eq!(options.days.into_iter().collect_vec(), [1, 3, 4, 5, 8]);
+        assert_eq!(options.parts, Parts::BOTH);
+
+        let options = parse_args(&args(&["all", "--part", "b"])).unwrap();
+        assert_eq!(options.days.len(), 25);
+        assert_eq!(options.parts, Parts { a: false, b: true });
+
+        let options = parse_args(&args(&["--part=A", "25"])).unwrap();
+        assert_eq!(options.parts, Parts { a: true, b: false });
+
+        assert!(parse_args(&args(&[])).is_err());
+        assert!(parse_args(&args(&["0"])).is_err());
+        assert!(parse_args(&args(&["26"])).is_err());
+        assert!(parse_args(&args(&["7-3"])).is_err());
+        assert!(parse_args(&args(&["1", "--part", "c"])).is_err());
+        assert!(parse_args(&args(&["1", "--verbose"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_timing() {
+        assert_eq!(parse_args(&args(&["1"])).unwrap().timing_runs, 0);
+        assert_eq!(parse_args(&args(&["1", "--time"])).unwrap().timing_runs, 1);
+        assert_eq!(
+            parse_args(&args(&["1", "--bench=5"])).unwrap().timing_runs,
+            5
+        );
+        assert_eq!(
+            parse_args(&args(&["1", "--bench", "5", "--time"]))
+                .unwrap()
+                .timing_runs,
+            5
+        );
+
+        assert!(parse_args(&args(&["1", "--bench", "0"])).is_err());
+        assert!(parse_args(&args(&["1", "--bench", "x"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(parse_args(&args(&["1"])).unwrap().format, Format::Text);
+        assert_eq!(
+            parse_args(&args(&["1", "--format", "json"]))
+                .unwrap()
+                .format,
+            Format::Json
+        );
+        assert_eq!(
+            parse_args(&args(&["1", "--format=csv"])).unwrap().format,
+            Format::Csv
+        );
+        assert!(parse_args(&args(&["1", "--format", "xml"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_inputs() {
+        let options = parse_args(&args(&["8", "--input", "-", "--input-dir=foo"])).unwrap();
+        assert_eq!(options.input_dir, Some(PathBuf::from("foo")));
+        assert_eq!(options.inputs, [(8, InputSource::Stdin)]);
+
+        let options = parse_args(&args(&["1-3", "--input=2=x=y", "--input", "3=-"])).unwrap();
+        assert_eq!(
+            options.inputs,
+            [
+                (2, InputSource::File("x=y".into())),
+                (3, InputSource::Stdin)
+            ]
+        );
+
+        assert!(parse_args(&args(&["1-3", "--input", "foo"])).is_err());
+        assert!(parse_args(&args(&["1", "--input"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_input() {
+        let days = once(20).collect();
+        assert_eq!(
+            parse_input("x.txt", &days).unwrap(),
+            (20, InputSource::File("x.txt".into()))
+        );
+        assert_eq!(
+            parse_input("20=-", &days).unwrap(),
+            (20, InputSource::Stdin)
+        );
+        assert!(parse_input("x.txt", &(1..=2).collect()).is_err());
+    }
+}
@@ -0,0 +1,196 @@
+//! Timing the parse and solve phases of a day over repeated runs.
+
+use crate::common::*;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Wall-clock time of one run of a day, split into its phases.
+///
+/// The input is read only once, before any measurement, so the parse time
+/// only covers [`Solver::parse`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Timings {
+    pub parse: Duration,
+    pub part_a: Option<Duration>,
+    pub part_b: Option<Duration>,
+}
+
+/// Names of the entries returned by [`Timings::phases`].
+pub const PHASES: [&str; 4] = ["parse", "part A", "part B", "total"];
+
+impl Timings {
+    pub fn total(&self) -> Duration {
+        self.parse + self.part_a.unwrap_or_default() + self.part_b.unwrap_or_default()
+    }
+
+    /// Returns the parse, part A, part B and total time, in that order.
+    pub fn phases(&self) -> [Option<Duration>; 4] {
+        [
+            Some(self.parse),
+            self.part_a,
+            self.part_b,
+            Some(self.total()),
+        ]
+    }
+}
+
+fn time<T>(fun: impl FnOnce() -> T) -> (T, Duration) {
+    let before = Instant::now();
+    let result = fun();
+    (result, before.elapsed())
+}
+
+/// Reads the input of solver `S` and solves the selected parts `n` times,
+/// see [`measure_input`].
+pub fn measure<S: Solver>(parts: Parts, n: usize) -> Result<(Solution, Vec<Timings>)> {
+    let input = read_input_string(&format!("day{:02}", S::DAY))?;
+    measure_input::<S>(&input, parts, n)
+}
+
+/// Parses `input` and solves the selected parts `n` times, and records the
+/// timings of each run. The returned solution is the one from the first run.
+pub fn measure_input<S: Solver>(
+    input: &str,
+    parts: Parts,
+    n: usize,
+) -> Result<(Solution, Vec<Timings>)> {
+    let mut solution = None;
+    let mut timings = vec![];
+
+    for _ in 0..n {
+        let (parsed, parse) = time(|| S::parse(input));
+        let parsed = parsed?;
+
+        let mut t = Timings { parse, ..default() };
+        let mut s = Solution::default();
+
+        if parts.a {
+            let (result, elapsed) = time(|| S::part_a(&parsed));
+            s.part_a = Some(result.context("while solving part A")?);
+            t.part_a = Some(elapsed);
+        }
+
+        if parts.b && S::HAS_PART_B {
+            let (result, elapsed) = time(|| S::part_b(&parsed));
+            s.part_b = Some(result.context("while solving part B")?);
+            t.part_b = Some(elapsed);
+        }
+
+        solution.get_or_insert(s);
+        timings.push(t);
+    }
+
+    Ok((solution.unwrap_or_default(), timings))
+}
+
+/// Minimum, median and maximum of a number of measurements.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    pub fn new(samples: impl IntoIterator<Item = Duration>) -> Option<Self> {
+        let mut samples = samples.into_iter().collect_vec();
+        samples.sort();
+
+        Some(Self {
+            min: *samples.first()?,
+            median: samples[samples.len() / 2],
+            max: *samples.last()?,
+        })
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "min {}, median {}, max {}",
+            FormatDuration(self.min),
+            FormatDuration(self.median),
+            FormatDuration(self.max)
+        )
+    }
+}
+
+/// Formats a duration using the most suitable unit.
+pub struct FormatDuration(pub Duration);
+
+impl fmt::Display for FormatDuration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let secs = self.0.as_secs_f64();
+
+        let s = if secs >= 1.0 {
+            format!("{:.2} s", secs)
+        } else if secs >= 1e-3 {
+            format!("{:.2} ms", secs * 1e3)
+        } else {
+            format!("{:.2} µs", secs * 1e6)
+        };
+
+        f.pad(&s)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let ms = Duration::from_millis;
+        let stats = Stats::new(vec![ms(5), ms(1), ms(3), ms(2)]).unwrap();
+
+        assert_eq!(stats.min, ms(1));
+        assert_eq!(stats.median, ms(3));
+        assert_eq!(stats.max, ms(5));
+        assert_eq!(Stats::new(vec![]), None);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        let f = |d| FormatDuration(d).to_string();
+
+        assert_eq!(f(Duration::from_micros(12)), "12.00 µs");
+        assert_eq!(f(Duration::from_micros(1500)), "1.50 ms");
+        assert_eq!(f(Duration::from_millis(2500)), "2.50 s");
+        assert_eq!(
+            format!("{:>10}", FormatDuration(Duration::from_secs(1))),
+            "    1.00 s"
+        );
+    }
+
+    #[test]
+    fn test_measure() {
+        struct Lines;
+
+        impl Solver for Lines {
+            const DAY: usize = 1;
+            type Input = Vec<String>;
+
+            fn parse(input: &str) -> Result<Self::Input> {
+                Ok(input.lines().map(str::to_string).collect())
+            }
+
+            fn part_a(lines: &Self::Input) -> Result<Answer> {
+                Ok(lines.len().into())
+            }
+
+            fn part_b(_: &Self::Input) -> Result<Answer> {
+                bail!("no part B")
+            }
+        }
+
+        let only_a = Parts { a: true, b: false };
+        let (solution, timings) = measure_input::<Lines>("1\n2\n3", only_a, 3).unwrap();
+
+        assert_eq!(solution.part_a, Some(3.into()));
+        assert_eq!(solution.part_b, None);
+        assert_eq!(timings.len(), 3);
+        assert!(all(&timings, |t| t.part_a.is_some() && t.part_b.is_none()));
+        assert!(measure_input::<Lines>("", Parts::BOTH, 1).is_err());
+    }
+}